@@ -1,3 +1,9 @@
+use crate::utility::vm::{InstructionSet, Machine, Registers, Step};
+
+pub mod differential;
+pub mod peephole;
+pub mod symbolic;
+
 type Literal = i64;
 type Register = char;
 
@@ -39,102 +45,155 @@ impl std::str::FromStr for Instruction {
             "inc" => Ok(Instruction::INC(words[1].parse()?)),
             "dec" => Ok(Instruction::DEC(words[1].parse()?)),
             "tgl" => Ok(Instruction::TGL(words[1].parse()?)),
-            "out" => Ok(Instruction::TGL(words[1].parse()?)),
+            "out" => Ok(Instruction::OUT(words[1].parse()?)),
             _ => Err("Unknown Instruction"),
         }
     }
 }
 
-const REGISTER_COUNT: usize = (b'z' - b'a') as usize;
+impl Instruction {
+    /// The instruction `tgl` turns this one into
+    pub fn toggled(&self) -> Instruction {
+        match *self {
+            Instruction::CPY(p1, p2) => Instruction::JNZ(p1, p2),
+            Instruction::JNZ(p1, p2) => Instruction::CPY(p1, p2),
+            Instruction::DEC(p1) => Instruction::INC(p1),
+            Instruction::INC(p1) => Instruction::DEC(p1),
+            Instruction::TGL(p1) => Instruction::INC(p1),
+            Instruction::OUT(p1) => Instruction::INC(p1),
+        }
+    }
+}
 
-#[derive(Default)]
-pub struct VirtualMachine {
-    instruction_ptr: usize,
-    registers: [Literal; REGISTER_COUNT],
+impl std::fmt::Display for Parameter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Parameter::Value(v) => write!(f, "{}", v),
+            Parameter::Address(r) => write!(f, "{}", r),
+        }
+    }
 }
 
-impl VirtualMachine {
-    pub fn run(&mut self, code: &mut Vec<Instruction>) -> Option<i64> {
-        self.instruction_ptr = 0;
-
-        while self.instruction_ptr < code.len() {
-            let instruction = code[self.instruction_ptr];
-
-            match instruction {
-                Instruction::CPY(from, to) => {
-                    if let Parameter::Address(t) = to {
-                        match from {
-                            Parameter::Address(a) => {
-                                *self.get_register_mut(t) = *self.get_register(a)
-                            }
-                            Parameter::Value(v) => *self.get_register_mut(t) = v,
-                        }
-                    }
-                    self.instruction_ptr += 1;
+impl std::fmt::Display for Instruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Instruction::CPY(p1, p2) => write!(f, "cpy {} {}", p1, p2),
+            Instruction::JNZ(p1, p2) => write!(f, "jnz {} {}", p1, p2),
+            Instruction::INC(p1) => write!(f, "inc {}", p1),
+            Instruction::DEC(p1) => write!(f, "dec {}", p1),
+            Instruction::TGL(p1) => write!(f, "tgl {}", p1),
+            Instruction::OUT(p1) => write!(f, "out {}", p1),
+        }
+    }
+}
+
+const REGISTER_COUNT: usize = (b'z' - b'a') as usize + 1;
+
+impl Parameter {
+    fn value(&self, registers: &Registers<Literal>) -> Literal {
+        match *self {
+            Parameter::Address(a) => registers[register_index(a)],
+            Parameter::Value(v) => v,
+        }
+    }
+}
+
+fn register_index(register: Register) -> usize {
+    register as usize - 'a' as usize
+}
+
+impl InstructionSet for Instruction {
+    type Value = Literal;
+
+    fn execute(
+        &self,
+        registers: &mut Registers<Literal>,
+        ip: usize,
+        code: &mut [Self],
+    ) -> Step<Literal> {
+        match *self {
+            Instruction::CPY(from, to) => {
+                if let Parameter::Address(t) = to {
+                    registers[register_index(t)] = from.value(registers);
                 }
-                Instruction::INC(p1) => {
-                    if let Parameter::Address(a) = p1 {
-                        *self.get_register_mut(a) += 1;
-                    }
-                    self.instruction_ptr += 1;
+                Step::Next
+            }
+            Instruction::INC(p1) => {
+                if let Parameter::Address(a) = p1 {
+                    registers[register_index(a)] += 1;
                 }
-                Instruction::DEC(p1) => {
-                    if let Parameter::Address(a) = p1 {
-                        *self.get_register_mut(a) -= 1;
-                    }
-                    self.instruction_ptr += 1;
+                Step::Next
+            }
+            Instruction::DEC(p1) => {
+                if let Parameter::Address(a) = p1 {
+                    registers[register_index(a)] -= 1;
                 }
-                Instruction::JNZ(p1, p2) => {
-                    let v = match p1 {
-                        Parameter::Address(a) => *self.get_register(a),
-                        Parameter::Value(v) => v,
-                    };
-
-                    let jmp = match p2 {
-                        Parameter::Address(a) => *self.get_register(a),
-                        Parameter::Value(v) => v,
-                    };
-
-                    if (v) != 0 {
-                        self.instruction_ptr = (self.instruction_ptr as i64 + jmp) as usize;
-                    } else {
-                        self.instruction_ptr += 1;
-                    }
+                Step::Next
+            }
+            Instruction::JNZ(p1, p2) => {
+                if p1.value(registers) != 0 {
+                    Step::Jump(p2.value(registers))
+                } else {
+                    Step::Next
                 }
-                Instruction::TGL(p1) => {
-                    if let Parameter::Address(r) = p1 {
-                        let index = (self.get_register(r) + (self.instruction_ptr as i64)) as usize;
-
-                        if index < code.len() {
-                            code[index] = match code[index] {
-                                Instruction::CPY(p1, p2) => Instruction::JNZ(p1, p2),
-                                Instruction::JNZ(p1, p2) => Instruction::CPY(p1, p2),
-                                Instruction::DEC(p1) => Instruction::INC(p1),
-                                Instruction::INC(p1) => Instruction::DEC(p1),
-                                Instruction::TGL(p1) => Instruction::INC(p1),
-                                Instruction::OUT(p1) => Instruction::INC(p1),
-                            };
-                        }
+            }
+            Instruction::TGL(p1) => {
+                if let Parameter::Address(r) = p1 {
+                    let index = (registers[register_index(r)] + ip as i64) as usize;
+
+                    if index < code.len() {
+                        code[index] = code[index].toggled();
                     }
-                    self.instruction_ptr += 1;
                 }
-                Instruction::OUT(p1) => match p1 {
-                    Parameter::Address(r) => return Some(*self.get_register(r)),
-                    Parameter::Value(v) => return Some(v),
-                },
+                Step::Next
             }
+            Instruction::OUT(p1) => Step::Output(p1.value(registers)),
         }
+    }
+}
+
+pub struct VirtualMachine {
+    machine: Machine<Literal>,
+}
 
-        None
+impl Default for VirtualMachine {
+    fn default() -> Self {
+        VirtualMachine {
+            machine: Machine::new(REGISTER_COUNT),
+        }
+    }
+}
+
+impl VirtualMachine {
+    pub fn run(&mut self, code: &mut [Instruction]) -> Option<i64> {
+        self.machine.reset();
+        self.machine.run(code)
+    }
+
+    /// Same as `run`, but add and multiply loops are applied in a single step
+    pub fn run_optimised(&mut self, code: &mut [Instruction]) -> Option<i64> {
+        self.machine.reset();
+
+        loop {
+            let ip = self.machine.instruction_ptr();
+            if let Some(fused) = peephole::fuse_loop(code, ip, &self.machine.registers) {
+                fused.apply(&mut self.machine.registers);
+                self.machine.skip(fused.length, fused.cycles);
+                continue;
+            }
+
+            match self.machine.step(code, &mut |_, _| {})? {
+                Step::Output(v) => return Some(v),
+                _ => continue,
+            }
+        }
     }
 
     pub fn get_register(&self, register: char) -> &Literal {
-        let index = register as usize - 'a' as usize;
-        &self.registers[index]
+        &self.machine.registers[register_index(register)]
     }
 
     pub fn get_register_mut(&mut self, register: char) -> &mut Literal {
-        let index = register as usize - 'a' as usize;
-        &mut self.registers[index]
+        &mut self.machine.registers[register_index(register)]
     }
 }
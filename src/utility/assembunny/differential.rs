@@ -0,0 +1,394 @@
+use crate::utility::vm::{Machine, Step};
+
+use super::{
+    Instruction, Literal, Parameter, REGISTER_COUNT, peephole,
+    symbolic::{Limits, Outcome, SymbolicMachine},
+};
+
+/// Small seeded xorshift generator, so that failures can be reproduced offline
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng(seed.max(1))
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Uniform-ish value in `0..n`
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    pub fn range(&mut self, lo: i64, hi: i64) -> i64 {
+        lo + self.below((hi - lo + 1) as usize) as i64
+    }
+}
+
+/// Observable result of running a program for a bounded number of steps
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trace {
+    pub registers: Vec<Literal>,
+    pub output: Vec<Literal>,
+    pub halted: bool,
+}
+
+/// An execution strategy for assembunny programs.
+/// Every backend starts with all registers at zero and executes at most `budget` instructions.
+pub trait Backend {
+    fn name(&self) -> &'static str;
+    fn execute(&self, code: &[Instruction], budget: usize) -> Trace;
+}
+
+/// Plain instruction by instruction interpreter
+pub struct Interpreter;
+
+/// Interpreter that applies add and multiply loops in one go
+pub struct Optimised;
+
+/// Symbolic machine without any unknown inputs
+pub struct Symbolic;
+
+impl Backend for Interpreter {
+    fn name(&self) -> &'static str {
+        "interpreter"
+    }
+
+    fn execute(&self, code: &[Instruction], budget: usize) -> Trace {
+        let mut code = code.to_vec();
+        let mut machine = Machine::new(REGISTER_COUNT);
+        let mut output = Vec::new();
+
+        while machine.cycle() < budget {
+            match machine.step(&mut code, &mut |_, _| {}) {
+                Some(Step::Output(v)) => output.push(v),
+                Some(_) => (),
+                None => break,
+            }
+        }
+
+        Trace {
+            halted: machine.halted() || machine.instruction_ptr() >= code.len(),
+            registers: machine.registers.iter().copied().collect(),
+            output,
+        }
+    }
+}
+
+impl Backend for Optimised {
+    fn name(&self) -> &'static str {
+        "optimised"
+    }
+
+    fn execute(&self, code: &[Instruction], budget: usize) -> Trace {
+        let mut code = code.to_vec();
+        let mut machine = Machine::new(REGISTER_COUNT);
+        let mut output = Vec::new();
+
+        while machine.cycle() < budget {
+            let ip = machine.instruction_ptr();
+            if let Some(fused) = peephole::fuse_loop(&code, ip, &machine.registers)
+                && machine.cycle() + fused.cycles <= budget
+            {
+                fused.apply(&mut machine.registers);
+                machine.skip(fused.length, fused.cycles);
+                continue;
+            }
+
+            match machine.step(&mut code, &mut |_, _| {}) {
+                Some(Step::Output(v)) => output.push(v),
+                Some(_) => (),
+                None => break,
+            }
+        }
+
+        Trace {
+            halted: machine.halted() || machine.instruction_ptr() >= code.len(),
+            registers: machine.registers.iter().copied().collect(),
+            output,
+        }
+    }
+}
+
+impl Backend for Symbolic {
+    fn name(&self) -> &'static str {
+        "symbolic"
+    }
+
+    fn execute(&self, code: &[Instruction], budget: usize) -> Trace {
+        let limits = Limits {
+            steps: budget,
+            outputs: usize::MAX,
+            paths: 1,
+        };
+        let path = SymbolicMachine::new(&[], 0..=0, limits)
+            .explore(code)
+            .pop()
+            .unwrap();
+
+        Trace {
+            registers: path.registers.iter().map(|r| r.eval(&[])).collect(),
+            output: path.outputs.iter().map(|o| o.eval(&[])).collect(),
+            halted: path.outcome == Outcome::Halted,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Config {
+    /// Number of instructions (templates count as several) in a generated program
+    pub length: usize,
+    /// Registers used by generated programs, starting from `a`
+    pub registers: usize,
+    /// Largest literal used in `cpy`
+    pub max_literal: i64,
+    /// Largest jump distance in either direction
+    pub max_jump: i64,
+    pub budget: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            length: 16,
+            registers: 4,
+            max_literal: 5,
+            max_jump: 4,
+            budget: 2_000,
+        }
+    }
+}
+
+fn random_register(rng: &mut Rng, config: &Config) -> Parameter {
+    Parameter::Address((b'a' + rng.below(config.registers) as u8) as char)
+}
+
+fn random_parameter(rng: &mut Rng, config: &Config) -> Parameter {
+    if rng.below(3) == 0 {
+        Parameter::Value(rng.range(-config.max_literal, config.max_literal))
+    } else {
+        random_register(rng, config)
+    }
+}
+
+/// Generates a random program. Besides single instructions it emits counted add and
+/// multiply loops, so that the optimised backend gets exercised. Any other backwards
+/// jump is cut short by the step budget.
+pub fn generate(rng: &mut Rng, config: &Config) -> Vec<Instruction> {
+    let mut code = Vec::new();
+
+    while code.len() < config.length {
+        let r = |rng: &mut Rng| random_register(rng, config);
+        let p = |rng: &mut Rng| random_parameter(rng, config);
+
+        match rng.below(9) {
+            0 => code.push(Instruction::CPY(p(rng), r(rng))),
+            1 => code.push(Instruction::INC(r(rng))),
+            2 => code.push(Instruction::DEC(r(rng))),
+            3 => {
+                let jump = rng.range(-config.max_jump, config.max_jump);
+                code.push(Instruction::JNZ(p(rng), Parameter::Value(jump)));
+            }
+            4 => code.push(Instruction::TGL(p(rng))),
+            5 => code.push(Instruction::OUT(p(rng))),
+            6 => {
+                let (x, y) = (r(rng), r(rng));
+                code.extend([
+                    Instruction::INC(x),
+                    Instruction::DEC(y),
+                    Instruction::JNZ(y, Parameter::Value(-2)),
+                ]);
+            }
+            7 => {
+                let (x, c, d) = (r(rng), r(rng), r(rng));
+                let times = rng.range(1, config.max_literal);
+                code.extend([
+                    Instruction::CPY(Parameter::Value(times), d),
+                    Instruction::CPY(p(rng), c),
+                    Instruction::INC(x),
+                    Instruction::DEC(c),
+                    Instruction::JNZ(c, Parameter::Value(-2)),
+                    Instruction::DEC(d),
+                    Instruction::JNZ(d, Parameter::Value(-5)),
+                ]);
+            }
+            _ => {
+                let value = rng.range(1, config.max_literal);
+                code.push(Instruction::CPY(Parameter::Value(value), r(rng)));
+            }
+        }
+    }
+
+    code
+}
+
+/// A program on which the backends disagree, with what each of them produced
+#[derive(Debug, Clone)]
+pub struct Divergence {
+    pub program: Vec<Instruction>,
+    pub traces: Vec<(&'static str, Trace)>,
+}
+
+impl std::fmt::Display for Divergence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for instruction in &self.program {
+            writeln!(f, "{}", instruction)?;
+        }
+        for (name, trace) in &self.traces {
+            writeln!(f, "{}: {:?}", name, trace)?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs the program on every backend, returning the traces if any two differ
+pub fn compare(
+    program: &[Instruction],
+    backends: &[&dyn Backend],
+    budget: usize,
+) -> Option<Divergence> {
+    let traces: Vec<_> = backends
+        .iter()
+        .map(|b| (b.name(), b.execute(program, budget)))
+        .collect();
+
+    traces
+        .windows(2)
+        .any(|w| w[0].1 != w[1].1)
+        .then(|| Divergence {
+            program: program.to_vec(),
+            traces,
+        })
+}
+
+fn simplify(parameter: Parameter) -> Option<Parameter> {
+    match parameter {
+        Parameter::Value(0) => None,
+        Parameter::Value(v) => Some(Parameter::Value(v - v.signum())),
+        Parameter::Address(_) => None,
+    }
+}
+
+/// Variants of an instruction with one literal moved closer to zero
+fn simplified(instruction: Instruction) -> Vec<Instruction> {
+    let mut variants = Vec::new();
+    match instruction {
+        Instruction::CPY(p1, p2) => {
+            variants.extend(simplify(p1).map(|p| Instruction::CPY(p, p2)));
+        }
+        Instruction::JNZ(p1, p2) => {
+            variants.extend(simplify(p1).map(|p| Instruction::JNZ(p, p2)));
+            variants.extend(simplify(p2).map(|p| Instruction::JNZ(p1, p)));
+        }
+        Instruction::TGL(p1) => variants.extend(simplify(p1).map(Instruction::TGL)),
+        Instruction::OUT(p1) => variants.extend(simplify(p1).map(Instruction::OUT)),
+        Instruction::INC(_) | Instruction::DEC(_) => (),
+    }
+    variants
+}
+
+/// Shrinks a diverging program by removing instructions and simplifying literals
+/// for as long as the backends still disagree on it.
+pub fn shrink(divergence: Divergence, backends: &[&dyn Backend], budget: usize) -> Divergence {
+    let mut current = divergence;
+
+    'outer: loop {
+        let program = &current.program;
+
+        // Remove chunks, largest first
+        let mut chunk = program.len() / 2;
+        while chunk > 0 {
+            for start in (0..program.len()).step_by(chunk) {
+                let mut candidate = program[..start].to_vec();
+                candidate.extend_from_slice(&program[(start + chunk).min(program.len())..]);
+
+                if let Some(smaller) = compare(&candidate, backends, budget) {
+                    current = smaller;
+                    continue 'outer;
+                }
+            }
+            chunk /= 2;
+        }
+
+        for (i, instruction) in program.iter().enumerate() {
+            for variant in simplified(*instruction) {
+                let mut candidate = program.clone();
+                candidate[i] = variant;
+
+                if let Some(simpler) = compare(&candidate, backends, budget) {
+                    current = simpler;
+                    continue 'outer;
+                }
+            }
+        }
+
+        return current;
+    }
+}
+
+/// Runs `iterations` random programs on every backend and returns the first
+/// divergence found, already shrunk.
+pub fn fuzz(
+    seed: u64,
+    iterations: usize,
+    config: &Config,
+    backends: &[&dyn Backend],
+) -> Option<Divergence> {
+    let mut rng = Rng::new(seed);
+
+    (0..iterations).find_map(|_| {
+        let program = generate(&mut rng, config);
+        compare(&program, backends, config.budget)
+            .map(|divergence| shrink(divergence, backends, config.budget))
+    })
+}
+
+#[test]
+fn backends_agree() {
+    let backends: [&dyn Backend; 3] = [&Interpreter, &Optimised, &Symbolic];
+    let divergence = fuzz(2016, 500, &Config::default(), &backends);
+
+    if let Some(divergence) = divergence {
+        panic!("Backends diverged:\n{}", divergence);
+    }
+}
+
+/// Interpreter that forgets to implement `tgl`
+struct NoToggle;
+
+impl Backend for NoToggle {
+    fn name(&self) -> &'static str {
+        "no toggle"
+    }
+
+    fn execute(&self, code: &[Instruction], budget: usize) -> Trace {
+        let code: Vec<_> = code
+            .iter()
+            .map(|i| match i {
+                Instruction::TGL(_) => Instruction::JNZ(Parameter::Value(0), Parameter::Value(0)),
+                other => *other,
+            })
+            .collect();
+        Interpreter.execute(&code, budget)
+    }
+}
+
+#[test]
+fn shrinks_divergence() {
+    let backends: [&dyn Backend; 2] = [&Interpreter, &NoToggle];
+    let divergence = fuzz(25, 100, &Config::default(), &backends).unwrap();
+
+    assert!(divergence.program.len() <= 4, "{}", divergence);
+    assert!(
+        divergence
+            .program
+            .iter()
+            .any(|i| matches!(i, Instruction::TGL(_)))
+    );
+}
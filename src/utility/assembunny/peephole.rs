@@ -0,0 +1,147 @@
+use crate::utility::vm::Registers;
+
+use super::{Instruction, Literal, Parameter, register_index};
+
+/// A loop recognised at the instruction pointer, whose whole effect can be applied at once
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FusedLoop {
+    /// Number of instructions the loop spans
+    pub length: usize,
+    /// Number of instructions the loop would have executed
+    pub cycles: usize,
+    /// Final register values
+    writes: Vec<(usize, Literal)>,
+}
+
+impl FusedLoop {
+    pub fn apply(&self, registers: &mut Registers<Literal>) {
+        for &(r, v) in &self.writes {
+            registers[r] = v;
+        }
+    }
+}
+
+fn register(parameter: Parameter) -> Option<usize> {
+    match parameter {
+        Parameter::Address(r) => Some(register_index(r)),
+        Parameter::Value(_) => None,
+    }
+}
+
+/// Matches `inc x; dec y; jnz y -2` (in either order, or with `dec x`).
+/// Returns `(x, y, sign)` where the loop adds `sign * y` to `x`.
+fn add_loop(code: &[Instruction]) -> Option<(usize, usize, Literal)> {
+    let (target, sign, counter) = match code {
+        [
+            Instruction::INC(x),
+            Instruction::DEC(y),
+            Instruction::JNZ(c, Parameter::Value(-2)),
+            ..,
+        ]
+        | [
+            Instruction::DEC(y),
+            Instruction::INC(x),
+            Instruction::JNZ(c, Parameter::Value(-2)),
+            ..,
+        ] => (register(*x)?, 1, (register(*y)?, register(*c)?)),
+        [
+            Instruction::DEC(x),
+            Instruction::DEC(y),
+            Instruction::JNZ(c, Parameter::Value(-2)),
+            ..,
+        ] => {
+            // Either register could be the counter, the jump decides which one
+            let (x, y, c) = (register(*x)?, register(*y)?, register(*c)?);
+            if c == x {
+                (y, -1, (x, c))
+            } else {
+                (x, -1, (y, c))
+            }
+        }
+        _ => return None,
+    };
+
+    let (y, c) = counter;
+    (y == c && target != y).then_some((target, y, sign))
+}
+
+/// Returns the loop starting at `ip` if it is an add or multiply loop that terminates
+/// from the current register values.
+pub fn fuse_loop(
+    code: &[Instruction],
+    ip: usize,
+    registers: &Registers<Literal>,
+) -> Option<FusedLoop> {
+    let code = code.get(ip..)?;
+
+    // cpy s c; <add loop on x, c>; dec d; jnz d -5
+    if let [
+        Instruction::CPY(source, Parameter::Address(c)),
+        _,
+        _,
+        _,
+        Instruction::DEC(Parameter::Address(d)),
+        Instruction::JNZ(Parameter::Address(d2), Parameter::Value(-5)),
+        ..,
+    ] = code
+        && let Some((x, counter, sign)) = add_loop(&code[1..])
+    {
+        let (c, d) = (register_index(*c), register_index(*d));
+        let s = match *source {
+            Parameter::Value(v) => Some(v),
+            Parameter::Address(r) => {
+                let r = register_index(r);
+                (r != x && r != c && r != d).then_some(registers[r])
+            }
+        };
+
+        if let Some(s) = s
+            && counter == c
+            && d == register_index(*d2)
+            && x != d
+            && c != d
+            && s > 0
+            && registers[d] > 0
+        {
+            let times = registers[d];
+            let delta = s.checked_mul(times)?.checked_mul(sign)?;
+            return Some(FusedLoop {
+                length: 6,
+                cycles: (times * (3 * s + 3)) as usize,
+                writes: vec![(x, registers[x].checked_add(delta)?), (c, 0), (d, 0)],
+            });
+        }
+    }
+
+    let (x, y, sign) = add_loop(code)?;
+    let times = registers[y];
+    (times > 0).then(|| FusedLoop {
+        length: 3,
+        cycles: 3 * times as usize,
+        writes: vec![(x, registers[x] + sign * times), (y, 0)],
+    })
+}
+
+#[test]
+fn fuses_multiply() {
+    let code: Vec<Instruction> = "cpy b c\ninc a\ndec c\njnz c -2\ndec d\njnz d -5"
+        .lines()
+        .map(|l| l.parse().unwrap())
+        .collect();
+
+    let mut registers = Registers::new(4);
+    registers[1] = 7;
+    registers[3] = 6;
+
+    let fused = fuse_loop(&code, 0, &registers).unwrap();
+    assert_eq!(fused.length, 6);
+    assert_eq!(fused.cycles, 6 * (1 + 3 * 7 + 2));
+
+    fused.apply(&mut registers);
+    assert_eq!(registers[0], 42);
+    assert_eq!(registers[2], 0);
+    assert_eq!(registers[3], 0);
+
+    // Never fused when the counter would not reach zero
+    assert_eq!(fuse_loop(&code, 0, &registers), None);
+}
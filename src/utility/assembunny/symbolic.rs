@@ -0,0 +1,789 @@
+use std::ops::RangeInclusive;
+
+use num::Rational64;
+
+use super::{Instruction, Literal, Parameter, REGISTER_COUNT, register_index};
+use crate::utility::{
+    ilp::{self, Relation},
+    linalg,
+    matrix::MatrixVec,
+};
+
+/// Affine expression `constant + sum(coefficients[i] * unknown_i)` over the unknowns of a path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Affine {
+    pub constant: Literal,
+    pub coefficients: Vec<Literal>,
+}
+
+impl Affine {
+    pub fn constant(value: Literal, inputs: usize) -> Self {
+        Affine {
+            constant: value,
+            coefficients: vec![0; inputs],
+        }
+    }
+
+    pub fn input(index: usize, inputs: usize) -> Self {
+        let mut expr = Self::constant(0, inputs);
+        expr.coefficients[index] = 1;
+        expr
+    }
+
+    /// Returns the value of the expression if it does not depend on any input
+    pub fn as_constant(&self) -> Option<Literal> {
+        self.coefficients
+            .iter()
+            .all(|c| *c == 0)
+            .then_some(self.constant)
+    }
+
+    pub fn eval(&self, values: &[Literal]) -> Literal {
+        self.coefficients
+            .iter()
+            .zip(values)
+            .fold(self.constant, |acc, (c, v)| acc + c * v)
+    }
+
+    /// Adds `factor * other` to the expression
+    fn add_scaled(&mut self, other: &Affine, factor: Literal) {
+        self.constant += factor * other.constant;
+        for (c, o) in self.coefficients.iter_mut().zip(&other.coefficients) {
+            *c += factor * o;
+        }
+    }
+
+    fn scaled(&self, factor: Literal) -> Affine {
+        let mut expr = Affine::constant(0, self.coefficients.len());
+        expr.add_scaled(self, factor);
+        expr
+    }
+}
+
+/// Why the exploration of a path stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Halted,
+    OutputLimit,
+    StepLimit,
+    /// A jump or toggle offset depended on an input that was not pinned down
+    Unsupported,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Limits {
+    /// Maximum instructions executed along a single path
+    pub steps: usize,
+    /// Stop a path after this many `out` instructions
+    pub outputs: usize,
+    /// Maximum number of paths returned by an exploration
+    pub paths: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            steps: 100_000,
+            outputs: 16,
+            paths: 10_000,
+        }
+    }
+}
+
+/// A single execution path, with the conditions the unknowns must meet to follow it.
+/// The unknowns are the inputs followed by the quotients and remainders of any
+/// division loops summarised along the way.
+#[derive(Debug, Clone)]
+pub struct Path {
+    /// Every expression here evaluates to zero on this path
+    pub equalities: Vec<Affine>,
+    /// Every expression here evaluates to something other than zero on this path.
+    /// Once the unknowns are pinned down these have been checked and are dropped.
+    pub disequalities: Vec<Affine>,
+    /// Every expression here is at least zero on this path, which is what makes
+    /// summarised loops terminate. Dropped along with the disequalities.
+    pub nonnegative: Vec<Affine>,
+    /// Allowed values of each unknown
+    pub ranges: Vec<RangeInclusive<Literal>>,
+    pub outputs: Vec<Affine>,
+    pub registers: Vec<Affine>,
+    pub outcome: Outcome,
+    /// Instructions executed, not counting loops summarised with an unknown pass count
+    pub steps: usize,
+    /// The unknowns, when the equalities pin them all down
+    pub solution: Option<Vec<Literal>>,
+}
+
+impl Path {
+    fn unknowns(&self) -> usize {
+        self.ranges.len()
+    }
+
+    fn satisfied_by(&self, values: &[Literal]) -> bool {
+        self.ranges.iter().zip(values).all(|(r, v)| r.contains(v))
+            && self.equalities.iter().all(|e| e.eval(values) == 0)
+            && self.disequalities.iter().all(|e| e.eval(values) != 0)
+            && self.nonnegative.iter().all(|e| e.eval(values) >= 0)
+    }
+
+    /// Adds an unknown restricted to `range` and returns its index
+    fn fresh(&mut self, range: RangeInclusive<Literal>) -> usize {
+        debug_assert!(self.solution.is_none());
+        for expr in self
+            .equalities
+            .iter_mut()
+            .chain(&mut self.disequalities)
+            .chain(&mut self.nonnegative)
+            .chain(&mut self.outputs)
+            .chain(&mut self.registers)
+        {
+            expr.coefficients.push(0);
+        }
+        self.ranges.push(range);
+        self.ranges.len() - 1
+    }
+}
+
+/// The unique solution of the equalities, if they pin every unknown down to integers.
+/// Returns None when they have no solution at all.
+fn pinned(equalities: &[Affine], unknowns: usize) -> Option<Option<Vec<Literal>>> {
+    let coefficients = equalities
+        .iter()
+        .flat_map(|e| e.coefficients.iter().map(|c| Rational64::from(*c)))
+        .collect();
+    let a = MatrixVec::from_vec(equalities.len(), unknowns, coefficients);
+    let b = equalities
+        .iter()
+        .map(|e| Rational64::from(-e.constant))
+        .collect::<Vec<_>>();
+
+    let solution = linalg::solve(&a, &b)?;
+    if !solution.null_space.is_empty() {
+        return Some(None);
+    }
+    if solution.particular.iter().all(|v| v.is_integer()) {
+        Some(Some(
+            solution.particular.iter().map(|v| v.to_integer()).collect(),
+        ))
+    } else {
+        None
+    }
+}
+
+/// Net change of each register over one pass of `body`, if it only increments and decrements
+fn flat_deltas(body: &[Instruction]) -> Option<Vec<Literal>> {
+    let mut deltas = vec![0; REGISTER_COUNT];
+    for instruction in body {
+        match *instruction {
+            Instruction::INC(Parameter::Address(r)) => deltas[register_index(r)] += 1,
+            Instruction::DEC(Parameter::Address(r)) => deltas[register_index(r)] -= 1,
+            _ => return None,
+        }
+    }
+    Some(deltas)
+}
+
+/// Matches the division loop
+/// `cpy k c; jnz b 2; jnz 1 6; dec b; dec c; jnz c -4; inc a; jnz 1 -7`,
+/// which adds `b / k` to `a`, leaves `k - b % k` in `c` and clears `b`.
+/// Returns `(k, a, b, c)`.
+fn division_loop(code: &[Instruction]) -> Option<(Parameter, usize, usize, usize)> {
+    let [
+        Instruction::CPY(k, Parameter::Address(c)),
+        Instruction::JNZ(Parameter::Address(b), Parameter::Value(2)),
+        Instruction::JNZ(Parameter::Value(exit), Parameter::Value(6)),
+        Instruction::DEC(Parameter::Address(b2)),
+        Instruction::DEC(Parameter::Address(c2)),
+        Instruction::JNZ(Parameter::Address(c3), Parameter::Value(-4)),
+        Instruction::INC(Parameter::Address(a)),
+        Instruction::JNZ(Parameter::Value(back), Parameter::Value(-7)),
+        ..,
+    ] = *code
+    else {
+        return None;
+    };
+
+    let (a, b, c) = (register_index(a), register_index(b), register_index(c));
+    (exit != 0
+        && back != 0
+        && b == register_index(b2)
+        && c == register_index(c2)
+        && c == register_index(c3)
+        && a != b
+        && a != c
+        && b != c)
+        .then_some((k, a, b, c))
+}
+
+#[derive(Clone)]
+struct State {
+    ip: usize,
+    code: Vec<Instruction>,
+    path: Path,
+}
+
+impl State {
+    fn read(&self, parameter: Parameter) -> Affine {
+        match parameter {
+            Parameter::Value(v) => Affine::constant(v, self.path.unknowns()),
+            Parameter::Address(r) => self.path.registers[register_index(r)].clone(),
+        }
+    }
+
+    /// Value of the expression if it is constant or the unknowns are already pinned
+    fn concrete(&self, expr: &Affine) -> Option<Literal> {
+        expr.as_constant()
+            .or_else(|| self.path.solution.as_ref().map(|s| expr.eval(s)))
+    }
+
+    fn concrete_parameter(&self, parameter: Parameter) -> Option<Literal> {
+        self.concrete(&self.read(parameter))
+    }
+
+    /// Runs the remaining passes of a loop whose body changes the registers by `deltas`,
+    /// until `counter` reaches zero, then continues at `exit`. `cycles(n)` is the number
+    /// of instructions `n` more passes take. Returns false when the counter never
+    /// reaches zero or the passes do not fit in `budget`.
+    fn repeat(
+        &mut self,
+        counter: usize,
+        deltas: &[Literal],
+        exit: usize,
+        budget: usize,
+        cycles: impl Fn(Literal) -> Literal,
+    ) -> bool {
+        let step = deltas[counter];
+        if step.abs() != 1 {
+            return false;
+        }
+
+        let passes = self.path.registers[counter].scaled(-step);
+        match self.concrete(&passes) {
+            Some(n) if n < 0 || cycles(n) as usize > budget => return false,
+            Some(n) => self.path.steps += cycles(n) as usize,
+            None => {
+                self.path.steps += cycles(0) as usize;
+                self.path.nonnegative.push(passes.clone());
+            }
+        }
+
+        // This also brings the counter down to zero
+        for (r, delta) in deltas.iter().enumerate() {
+            if *delta != 0 {
+                self.path.registers[r].add_scaled(&passes, *delta);
+            }
+        }
+        self.ip = exit;
+        true
+    }
+
+    /// Division loop at the instruction pointer, see `division_loop`. An unknown
+    /// dividend gets a fresh unknown for each of the quotient and remainder.
+    fn divide(&mut self, budget: usize) -> bool {
+        let Some((k, a, b, c)) = division_loop(&self.code[self.ip..]) else {
+            return false;
+        };
+        let Some(k) = self.concrete_parameter(k).filter(|k| *k > 0) else {
+            return false;
+        };
+
+        let cycles = |q: Literal, r: Literal| 3 + q * (4 * k + 3) + 4 * r;
+        let (quotient, remainder) = match self.concrete(&self.path.registers[b]) {
+            Some(v) if v < 0 || cycles(v / k, v % k) as usize > budget => return false,
+            Some(v) => {
+                self.path.steps += cycles(v / k, v % k) as usize;
+                let n = self.path.unknowns();
+                (Affine::constant(v / k, n), Affine::constant(v % k, n))
+            }
+            None => {
+                self.path.steps += cycles(0, 0) as usize;
+                let q = self.path.fresh(0..=Literal::MAX);
+                let r = self.path.fresh(0..=k - 1);
+                let n = self.path.unknowns();
+                let (q, r) = (Affine::input(q, n), Affine::input(r, n));
+
+                let mut equality = self.path.registers[b].clone();
+                equality.add_scaled(&q, -k);
+                equality.add_scaled(&r, -1);
+                self.path.equalities.push(equality);
+                (q, r)
+            }
+        };
+
+        let n = self.path.unknowns();
+        self.path.registers[a].add_scaled(&quotient, 1);
+        self.path.registers[b] = Affine::constant(0, n);
+        self.path.registers[c] = Affine::constant(k, n);
+        self.path.registers[c].add_scaled(&remainder, -1);
+        self.ip += 8;
+        true
+    }
+
+    /// Net change of each register over one pass of `body`, with the number of
+    /// instructions the pass executes. The body either only increments and decrements,
+    /// or wraps one such loop as `cpy s c; <body>; jnz c -n; <body>` with `s` known.
+    fn pass(&self, body: &[Instruction]) -> Option<(Vec<Literal>, Literal)> {
+        if let Some(deltas) = flat_deltas(body) {
+            return Some((deltas, body.len() as Literal));
+        }
+
+        let [Instruction::CPY(s, Parameter::Address(c)), ..] = *body else {
+            return None;
+        };
+        let c = register_index(c);
+        let close = body.iter().position(|i| {
+            matches!(*i, Instruction::JNZ(Parameter::Address(r), Parameter::Value(_))
+                if register_index(r) == c)
+        })?;
+        let Instruction::JNZ(_, Parameter::Value(offset)) = body[close] else {
+            return None;
+        };
+
+        let inner = flat_deltas(&body[1..close])?;
+        let tail = flat_deltas(&body[close + 1..])?;
+        let source_fixed = match s {
+            Parameter::Value(_) => true,
+            Parameter::Address(r) => {
+                let r = register_index(r);
+                r != c && inner[r] == 0 && tail[r] == 0
+            }
+        };
+        if offset != 1 - close as Literal || inner[c].abs() != 1 || tail[c] != 0 || !source_fixed {
+            return None;
+        }
+
+        // The inner loop runs its body at least once, so its counter must start on
+        // the side of zero it steps towards
+        let passes = self.concrete_parameter(s)? * -inner[c];
+        if passes < 1 {
+            return None;
+        }
+
+        let mut deltas: Vec<Literal> = inner
+            .iter()
+            .zip(&tail)
+            .map(|(i, t)| passes * i + t)
+            .collect();
+        deltas[c] = 0;
+        let length = 1 + passes * close as Literal + (body.len() - close - 1) as Literal;
+        Some((deltas, length))
+    }
+
+    /// Replaces the loop at the instruction pointer by its closed form, if it is one
+    /// of the shapes below and fits in `budget`:
+    /// - `jnz c -n` closing a body of only `inc` and `dec`, or of one nested loop
+    /// - `jnz c 2; jnz 1 n` guarding such a body followed by a jump back
+    /// - the division loop of `division_loop`
+    fn summarise(&mut self, budget: usize) -> bool {
+        let ip = self.ip;
+        match self.code[ip] {
+            Instruction::JNZ(Parameter::Address(c), Parameter::Value(offset)) if offset < 0 => {
+                let Some(start) = ip.checked_sub(offset.unsigned_abs() as usize) else {
+                    return false;
+                };
+                let Some((deltas, length)) = self.pass(&self.code[start..ip]) else {
+                    return false;
+                };
+                self.repeat(register_index(c), &deltas, ip + 1, budget, |n| {
+                    n * (length + 1) + 1
+                })
+            }
+            Instruction::JNZ(Parameter::Address(c), Parameter::Value(2)) => {
+                let Some(&Instruction::JNZ(Parameter::Value(exit), Parameter::Value(skip))) =
+                    self.code.get(ip + 1)
+                else {
+                    return false;
+                };
+                let end = ip + skip.max(0) as usize;
+                let closes = matches!(
+                    self.code.get(end),
+                    Some(&Instruction::JNZ(Parameter::Value(back), Parameter::Value(offset)))
+                        if back != 0 && offset == -skip
+                );
+                if exit == 0 || skip < 3 || !closes {
+                    return false;
+                }
+                let Some(deltas) = flat_deltas(&self.code[ip + 2..end]) else {
+                    return false;
+                };
+                let length = (end - ip - 2) as Literal;
+                self.repeat(register_index(c), &deltas, end + 1, budget, |n| {
+                    n * (length + 2) + 2
+                })
+            }
+            Instruction::CPY(..) => self.divide(budget),
+            _ => false,
+        }
+    }
+}
+
+/// Symbolic interpreter where the registers named in `inputs` start as unknowns
+/// and every other register starts at zero.
+pub struct SymbolicMachine {
+    inputs: Vec<char>,
+    bounds: RangeInclusive<Literal>,
+    limits: Limits,
+}
+
+impl SymbolicMachine {
+    /// Unknown inputs are restricted to `bounds`, paths needing values outside are pruned
+    pub fn new(inputs: &[char], bounds: RangeInclusive<Literal>, limits: Limits) -> Self {
+        SymbolicMachine {
+            inputs: inputs.to_vec(),
+            bounds,
+            limits,
+        }
+    }
+
+    fn start(&self, code: &[Instruction], solution: Option<Vec<Literal>>) -> State {
+        let n = self.inputs.len();
+        let mut registers = vec![Affine::constant(0, n); REGISTER_COUNT];
+        for (i, r) in self.inputs.iter().enumerate() {
+            registers[register_index(*r)] = Affine::input(i, n);
+        }
+
+        State {
+            ip: 0,
+            code: code.to_vec(),
+            path: Path {
+                equalities: Vec::new(),
+                disequalities: Vec::new(),
+                nonnegative: Vec::new(),
+                ranges: vec![self.bounds.clone(); n],
+                outputs: Vec::new(),
+                registers,
+                outcome: Outcome::Halted,
+                steps: 0,
+                solution,
+            },
+        }
+    }
+
+    /// Explores every feasible path through the program, forking at each `jnz`
+    /// whose condition depends on an input that is not pinned down yet.
+    /// Counted loops are summarised in closed form rather than unrolled, so they
+    /// fork once per exit instead of once per pass.
+    pub fn explore(&self, code: &[Instruction]) -> Vec<Path> {
+        let mut stack = vec![self.start(code, None)];
+        let mut finished = Vec::new();
+
+        while let Some(mut state) = stack.pop() {
+            if finished.len() >= self.limits.paths {
+                break;
+            }
+            let outcome = self.run_path(&mut state, &mut stack);
+            state.path.outcome = outcome;
+            finished.push(state.path);
+        }
+
+        finished
+    }
+
+    /// Runs the program with the inputs set to `values`, such as `a` for a given day 23 input
+    pub fn run(&self, code: &[Instruction], values: &[Literal]) -> Path {
+        assert_eq!(values.len(), self.inputs.len());
+        let mut state = self.start(code, Some(values.to_vec()));
+        state.path.outcome = self.run_path(&mut state, &mut Vec::new());
+        state.path
+    }
+
+    fn run_path(&self, state: &mut State, stack: &mut Vec<State>) -> Outcome {
+        while state.ip < state.code.len() {
+            if state.path.steps >= self.limits.steps {
+                return Outcome::StepLimit;
+            }
+            if state.summarise(self.limits.steps - state.path.steps) {
+                continue;
+            }
+            state.path.steps += 1;
+
+            match state.code[state.ip] {
+                Instruction::CPY(from, Parameter::Address(t)) => {
+                    state.path.registers[register_index(t)] = state.read(from);
+                }
+                Instruction::INC(Parameter::Address(a)) => {
+                    state.path.registers[register_index(a)].constant += 1;
+                }
+                Instruction::DEC(Parameter::Address(a)) => {
+                    state.path.registers[register_index(a)].constant -= 1;
+                }
+                Instruction::JNZ(p1, p2) => {
+                    let Some(offset) = state.concrete_parameter(p2) else {
+                        return Outcome::Unsupported;
+                    };
+
+                    let jump = match state.concrete_parameter(p1) {
+                        Some(v) => v != 0,
+                        None => {
+                            // Fork: the zero branch is pushed for later, we carry on jumping
+                            let condition = state.read(p1);
+                            if let Some(zero) = self.fork_zero(state, condition.clone()) {
+                                stack.push(zero);
+                            }
+
+                            state.path.disequalities.push(condition);
+                            true
+                        }
+                    };
+
+                    if jump {
+                        let target = state.ip as i64 + offset;
+                        if target < 0 {
+                            return Outcome::Halted;
+                        }
+                        state.ip = target as usize;
+                        continue;
+                    }
+                }
+                Instruction::TGL(p1) => {
+                    let Some(offset) = state.concrete_parameter(p1) else {
+                        return Outcome::Unsupported;
+                    };
+                    if let Parameter::Address(_) = p1 {
+                        let index = (state.ip as i64 + offset) as usize;
+                        if index < state.code.len() {
+                            state.code[index] = state.code[index].toggled();
+                        }
+                    }
+                }
+                Instruction::OUT(p1) => {
+                    let value = state.read(p1);
+                    state.path.outputs.push(value);
+                    if state.path.outputs.len() >= self.limits.outputs {
+                        state.ip += 1;
+                        return Outcome::OutputLimit;
+                    }
+                }
+                // Writes to literals are skipped, as in the concrete machine
+                _ => (),
+            }
+
+            state.ip += 1;
+        }
+
+        Outcome::Halted
+    }
+
+    /// Branch of a `jnz` where `condition` is zero, or None if no unknowns in range can take it
+    fn fork_zero(&self, state: &State, condition: Affine) -> Option<State> {
+        let mut zero = State {
+            ip: state.ip + 1,
+            code: state.code.clone(),
+            path: state.path.clone(),
+        };
+        zero.path.equalities.push(condition);
+
+        match pinned(&zero.path.equalities, zero.path.unknowns())? {
+            Some(values) => {
+                if !zero.path.satisfied_by(&values) {
+                    return None;
+                }
+                zero.path.disequalities.clear();
+                zero.path.nonnegative.clear();
+                zero.path.solution = Some(values);
+            }
+            None => {
+                self.assignment(&zero.path)?;
+            }
+        }
+
+        Some(zero)
+    }
+
+    /// Values of all the unknowns that follow `path`, with the inputs as small as
+    /// possible in lexicographic order. A disequality broken by the optimum splits
+    /// the search into the sides where it is negative and positive.
+    fn assignment(&self, path: &Path) -> Option<Vec<Literal>> {
+        if let Some(solution) = &path.solution {
+            return path.satisfied_by(solution).then(|| solution.clone());
+        }
+
+        let inputs = self.inputs.len();
+        let mut best: Option<Vec<Literal>> = None;
+        let mut stack = vec![Vec::new()];
+
+        while let Some(splits) = stack.pop() {
+            let Some(values) = self.lowest(path, &splits) else {
+                continue;
+            };
+
+            match path.disequalities.iter().find(|e| e.eval(&values) == 0) {
+                Some(broken) => {
+                    for sign in [-1, 1] {
+                        let mut side = broken.scaled(sign);
+                        side.constant -= 1;
+                        let mut splits = splits.clone();
+                        splits.push(side);
+                        stack.push(splits);
+                    }
+                }
+                None => {
+                    if best.as_ref().is_none_or(|b| values[..inputs] < b[..inputs]) {
+                        best = Some(values);
+                    }
+                }
+            }
+        }
+
+        best
+    }
+
+    /// Lexicographically smallest inputs meeting the equalities, ranges and the
+    /// non-negative expressions of `path` plus `extra`, ignoring the disequalities
+    fn lowest(&self, path: &Path, extra: &[Affine]) -> Option<Vec<Literal>> {
+        let n = path.unknowns();
+        if n == 0 {
+            return path.satisfied_by(&[]).then(Vec::new);
+        }
+
+        // Each input in turn is minimised and then held at its minimum
+        let mut ranges = path.ranges.clone();
+        let mut values = Vec::new();
+        for i in 0..self.inputs.len() {
+            let mut objective = vec![0; n];
+            objective[i] = 1;
+
+            let mut problem = ilp::Problem::minimize(&objective);
+            for (j, range) in ranges.iter().enumerate() {
+                let upper = (*range.end() < Literal::MAX).then_some(*range.end());
+                problem = problem.bounds(j, *range.start(), upper);
+            }
+            for e in &path.equalities {
+                problem = problem.constraint(&e.coefficients, Relation::Equal, -e.constant);
+            }
+            for e in path.nonnegative.iter().chain(extra) {
+                problem = problem.constraint(&e.coefficients, Relation::GreaterEq, -e.constant);
+            }
+
+            let ilp::Outcome::Optimal { value, values: v } = problem.solve() else {
+                return None;
+            };
+            ranges[i] = value..=value;
+            values = v;
+        }
+        Some(values)
+    }
+
+    /// Finds the smallest input values whose output stream starts with `expected`.
+    /// Each path's outputs are affine in its unknowns, so matching them is one more
+    /// set of equalities and the inputs come out of an integer program rather than
+    /// from trying every value in range.
+    pub fn solve(&self, code: &[Instruction], expected: &[Literal]) -> Option<Vec<Literal>> {
+        self.explore(code)
+            .into_iter()
+            .filter(|path| path.outcome != Outcome::Unsupported)
+            .filter(|path| path.outputs.len() >= expected.len())
+            .filter_map(|mut path| {
+                for (output, value) in path.outputs.iter().zip(expected) {
+                    let mut equality = output.clone();
+                    equality.constant -= value;
+                    path.equalities.push(equality);
+                }
+                let mut values = self.assignment(&path)?;
+                values.truncate(self.inputs.len());
+                Some(values)
+            })
+            .min()
+    }
+}
+
+fn parse(code: &str) -> Vec<Instruction> {
+    code.lines().map(|l| l.parse().unwrap()).collect()
+}
+
+#[test]
+fn affine_registers() {
+    let code = parse("cpy a b\ninc b\ninc b\ncpy b c\ndec c");
+    let machine = SymbolicMachine::new(&['a'], 0..=100, Limits::default());
+    let paths = machine.explore(&code);
+
+    assert_eq!(paths.len(), 1);
+    assert_eq!(
+        paths[0].registers[2],
+        Affine {
+            constant: 1,
+            coefficients: vec![1]
+        }
+    );
+    assert_eq!(paths[0].outcome, Outcome::Halted);
+}
+
+#[test]
+fn path_conditions() {
+    // Outputs 1 if a == 3, otherwise 0
+    let code = parse("cpy a b\ndec b\ndec b\ndec b\njnz b 3\nout 1\njnz 1 2\nout 0");
+    let machine = SymbolicMachine::new(&['a'], 0..=100, Limits::default());
+
+    let paths = machine.explore(&code);
+    assert_eq!(paths.len(), 2);
+
+    let found = machine.solve(&code, &[1]);
+    assert_eq!(found, Some(vec![3]));
+
+    let found = machine.solve(&code, &[0]);
+    assert_eq!(found, Some(vec![0]));
+}
+
+#[test]
+fn clock_signal() {
+    let code = parse(
+        "cpy a d\ncpy 7 c\ncpy 365 b\ninc d\ndec b\njnz b -2\ndec c\njnz c -5\ncpy d a\n\
+         jnz 0 0\ncpy a b\ncpy 0 a\ncpy 2 c\njnz b 2\njnz 1 6\ndec b\ndec c\njnz c -4\n\
+         inc a\njnz 1 -7\ncpy 2 b\njnz c 2\njnz 1 4\ndec b\ndec c\njnz 1 -4\njnz 0 0\n\
+         out b\njnz a -19\njnz 1 -21",
+    );
+
+    let limits = Limits {
+        outputs: 12,
+        ..Limits::default()
+    };
+    let machine = SymbolicMachine::new(&['a'], 0..=200, limits);
+    let signal: Vec<Literal> = (0..12).map(|i| i % 2).collect();
+    assert_eq!(machine.solve(&code, &signal), Some(vec![175]));
+
+    // The division loops are summarised, so only the path that exits after the
+    // twelfth bit survives instead of one fork per decrement
+    assert_eq!(machine.explore(&code).len(), 1);
+}
+
+#[test]
+fn loop_summaries() {
+    // c = 2 * a, counted down in a loop
+    let code = parse("cpy a b\ninc c\ninc c\ndec b\njnz b -3");
+    let machine = SymbolicMachine::new(&['a'], 0..=100, Limits::default());
+    let paths = machine.explore(&code);
+
+    assert_eq!(paths.len(), 1);
+    assert_eq!(paths[0].registers[2], Affine::input(0, 1).scaled(2));
+    assert_eq!(paths[0].registers[1], Affine::constant(0, 1));
+
+    // With a given input the summary still counts every instruction of the loop
+    let path = machine.run(&code, &[50]);
+    assert_eq!(path.registers[2].eval(&[50]), 100);
+    assert_eq!(path.steps, 1 + 3 + 49 * 4 + 1);
+}
+
+#[test]
+fn toggled_factorial() {
+    // Day 23 shape: a factorial whose outer loop ends by toggling its own jump
+    let code = parse(
+        "cpy a b\ndec b\ncpy a d\ncpy 0 a\ncpy b c\ninc a\ndec c\njnz c -2\ndec d\n\
+         jnz d -5\ndec b\ncpy b c\ncpy c d\ndec d\ninc c\njnz d -2\ntgl c\ncpy -16 c\n\
+         jnz 1 c\ncpy 6 c\njnz 7 d\ninc a\ninc d\njnz d -2\ninc c\njnz c -5",
+    );
+    let limits = Limits {
+        steps: usize::MAX,
+        ..Limits::default()
+    };
+    let machine = SymbolicMachine::new(&['a'], 0..=20, limits);
+
+    for a in [7, 12] {
+        let path = machine.run(&code, &[a]);
+        assert_eq!(path.outcome, Outcome::Halted);
+
+        let mut vm = super::VirtualMachine::default();
+        *vm.get_register_mut('a') = a;
+        vm.run_optimised(&mut code.clone());
+        assert_eq!(path.registers[0].eval(&[a]), *vm.get_register('a'));
+    }
+}
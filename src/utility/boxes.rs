@@ -0,0 +1,226 @@
+/// Axis aligned box of integer cells in N dimensions, `min` is inclusive and `max` exclusive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Cuboid<const N: usize> {
+    pub min: [i64; N],
+    pub max: [i64; N],
+}
+
+pub type Rect = Cuboid<2>;
+
+impl<const N: usize> Cuboid<N> {
+    pub fn new(min: [i64; N], max: [i64; N]) -> Self {
+        Cuboid { min, max }
+    }
+
+    /// Box covering every cell from `min` to `max`, both inclusive
+    pub fn inclusive(min: [i64; N], max: [i64; N]) -> Self {
+        Cuboid {
+            min,
+            max: max.map(|v| v + 1),
+        }
+    }
+
+    /// Smallest box with both points as opposite corners, both inclusive
+    pub fn spanning(a: [i64; N], b: [i64; N]) -> Self {
+        Self::inclusive(
+            std::array::from_fn(|i| a[i].min(b[i])),
+            std::array::from_fn(|i| a[i].max(b[i])),
+        )
+    }
+
+    pub fn is_empty(&self) -> bool {
+        (0..N).any(|i| self.min[i] >= self.max[i])
+    }
+
+    pub fn size(&self, axis: usize) -> i64 {
+        (self.max[axis] - self.min[axis]).max(0)
+    }
+
+    /// Number of cells, N dimensional volume
+    pub fn volume(&self) -> i64 {
+        (0..N).map(|i| self.size(i)).product()
+    }
+
+    pub fn contains_point(&self, p: [i64; N]) -> bool {
+        (0..N).all(|i| self.min[i] <= p[i] && p[i] < self.max[i])
+    }
+
+    pub fn contains(&self, other: &Cuboid<N>) -> bool {
+        other.is_empty()
+            || (0..N).all(|i| self.min[i] <= other.min[i] && other.max[i] <= self.max[i])
+    }
+
+    pub fn intersection(&self, other: &Cuboid<N>) -> Option<Cuboid<N>> {
+        let out = Cuboid {
+            min: std::array::from_fn(|i| self.min[i].max(other.min[i])),
+            max: std::array::from_fn(|i| self.max[i].min(other.max[i])),
+        };
+        (!out.is_empty()).then_some(out)
+    }
+
+    /// This box minus other as at most 2N disjoint boxes
+    pub fn subtract(&self, other: &Cuboid<N>) -> Vec<Cuboid<N>> {
+        let Some(cut) = self.intersection(other) else {
+            return vec![*self];
+        };
+
+        // Peel off the slabs below and above the cut one axis at a time
+        let mut pieces = Vec::new();
+        let mut rest = *self;
+        for axis in 0..N {
+            if rest.min[axis] < cut.min[axis] {
+                let mut below = rest;
+                below.max[axis] = cut.min[axis];
+                pieces.push(below);
+            }
+            if cut.max[axis] < rest.max[axis] {
+                let mut above = rest;
+                above.min[axis] = cut.max[axis];
+                pieces.push(above);
+            }
+            rest.min[axis] = cut.min[axis];
+            rest.max[axis] = cut.max[axis];
+        }
+        pieces
+    }
+}
+
+/// Union of boxes kept as disjoint pieces, for reactor style on/off sequences
+#[derive(Debug, Clone, Default)]
+pub struct BoxSet<const N: usize> {
+    pieces: Vec<Cuboid<N>>,
+}
+
+impl<const N: usize> BoxSet<N> {
+    pub fn new() -> Self {
+        BoxSet { pieces: Vec::new() }
+    }
+
+    pub fn pieces(&self) -> &[Cuboid<N>] {
+        &self.pieces
+    }
+
+    pub fn insert(&mut self, cuboid: Cuboid<N>) {
+        if cuboid.is_empty() {
+            return;
+        }
+        self.remove(cuboid);
+        self.pieces.push(cuboid);
+    }
+
+    pub fn remove(&mut self, cuboid: Cuboid<N>) {
+        self.pieces = self
+            .pieces
+            .iter()
+            .flat_map(|p| p.subtract(&cuboid))
+            .collect();
+    }
+
+    pub fn volume(&self) -> i64 {
+        self.pieces.iter().map(|p| p.volume()).sum()
+    }
+
+    pub fn contains_point(&self, p: [i64; N]) -> bool {
+        self.pieces.iter().any(|c| c.contains_point(p))
+    }
+
+    /// Whether every cell of the box is covered by the union
+    pub fn covers(&self, cuboid: &Cuboid<N>) -> bool {
+        let covered: i64 = self
+            .pieces
+            .iter()
+            .filter_map(|p| p.intersection(cuboid))
+            .map(|c| c.volume())
+            .sum();
+        covered == cuboid.volume()
+    }
+}
+
+impl<const N: usize> FromIterator<Cuboid<N>> for BoxSet<N> {
+    fn from_iter<I: IntoIterator<Item = Cuboid<N>>>(iter: I) -> Self {
+        let mut set = BoxSet::new();
+        for c in iter {
+            set.insert(c);
+        }
+        set
+    }
+}
+
+/// Measure of the union of the boxes restricted to the axes from `axis` onwards,
+/// sweeping the first of those axes and recursing on the boxes active in each slab
+fn sweep_volume<const N: usize>(boxes: &[&Cuboid<N>], axis: usize) -> i64 {
+    if axis == N {
+        return (!boxes.is_empty()) as i64;
+    }
+
+    let mut edges = boxes
+        .iter()
+        .flat_map(|b| [b.min[axis], b.max[axis]])
+        .collect::<Vec<_>>();
+    edges.sort();
+    edges.dedup();
+
+    edges
+        .windows(2)
+        .map(|w| {
+            let active = boxes
+                .iter()
+                .filter(|b| b.min[axis] <= w[0] && w[1] <= b.max[axis])
+                .copied()
+                .collect::<Vec<_>>();
+            if active.is_empty() {
+                0
+            } else {
+                (w[1] - w[0]) * sweep_volume(&active, axis + 1)
+            }
+        })
+        .sum()
+}
+
+/// Volume covered by at least one of the boxes
+pub fn union_volume<const N: usize>(boxes: &[Cuboid<N>]) -> i64 {
+    let boxes = boxes.iter().filter(|b| !b.is_empty()).collect::<Vec<_>>();
+    sweep_volume(&boxes, 0)
+}
+
+#[test]
+fn cuboid_algebra() {
+    let a = Cuboid::inclusive([0, 0, 0], [9, 9, 9]);
+    let b = Cuboid::new([5, 5, 5], [15, 15, 15]);
+
+    assert_eq!(a.volume(), 1000);
+    assert_eq!(a.intersection(&b).unwrap().volume(), 125);
+    assert_eq!(a.intersection(&Cuboid::new([10, 0, 0], [12, 5, 5])), None);
+
+    let pieces = a.subtract(&b);
+    assert_eq!(pieces.len(), 3);
+    assert_eq!(pieces.iter().map(|p| p.volume()).sum::<i64>(), 875);
+    assert!(pieces.iter().all(|p| p.intersection(&b).is_none()));
+
+    assert_eq!(union_volume(&[a, b]), 1875);
+    assert_eq!(Rect::spanning([3, 1], [1, 4]), Rect::new([1, 1], [4, 5]));
+}
+
+#[test]
+fn reactor() {
+    // Example from the small reactor reboot: on, on, off, on
+    let steps = [
+        (true, [10, 10, 10], [12, 12, 12]),
+        (true, [11, 11, 11], [13, 13, 13]),
+        (false, [9, 9, 9], [11, 11, 11]),
+        (true, [10, 10, 10], [10, 10, 10]),
+    ];
+
+    let mut set = BoxSet::new();
+    for (on, min, max) in steps {
+        let c = Cuboid::inclusive(min, max);
+        if on { set.insert(c) } else { set.remove(c) }
+    }
+
+    assert_eq!(set.volume(), 39);
+    assert!(set.contains_point([13, 13, 13]));
+    assert!(!set.contains_point([11, 11, 11]));
+    assert!(set.covers(&Cuboid::inclusive([12, 12, 12], [13, 13, 13])));
+    assert!(!set.covers(&Cuboid::inclusive([10, 10, 10], [11, 11, 11])));
+    assert_eq!(union_volume(set.pieces()), 39);
+}
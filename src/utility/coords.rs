@@ -0,0 +1,321 @@
+use std::ops::{Add, Mul};
+
+use crate::utility::{directions::IVec2, matrix::MatrixVec};
+
+/// Sum of the absolute differences, the number of orthogonal steps between a and b
+pub fn manhattan(a: IVec2, b: IVec2) -> i64 {
+    (a - b).abs().sum()
+}
+
+/// Largest absolute difference, the number of king moves between a and b
+pub fn chebyshev(a: IVec2, b: IVec2) -> i64 {
+    (a - b).abs().max()
+}
+
+/// Square spiral starting at the origin with 1, going right and then turning left:
+///
+/// ```text
+/// 5 4 3
+/// 6 1 2
+/// 7 8 9
+/// ```
+/// With y pointing up, so 3 is at (1, 1) and 9 at (1, -1).
+#[derive(Clone, Copy, Debug)]
+pub struct Spiral {
+    position: IVec2,
+    direction: IVec2,
+    steps_left: i64,
+    step_size: i64,
+    turns: i64,
+}
+
+impl Spiral {
+    pub fn new() -> Self {
+        Spiral {
+            position: IVec2::new(0, 0),
+            direction: IVec2::new(1, 0),
+            steps_left: 1,
+            step_size: 1,
+            turns: 0,
+        }
+    }
+}
+
+impl Default for Spiral {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Iterator for Spiral {
+    type Item = IVec2;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let pos = self.position;
+
+        self.position += self.direction;
+        self.steps_left -= 1;
+
+        if self.steps_left == 0 {
+            self.direction = IVec2::new(-self.direction.y, self.direction.x);
+
+            self.turns += 1;
+            if self.turns % 2 == 0 {
+                self.step_size += 1;
+            }
+            self.steps_left = self.step_size;
+        }
+
+        Some(pos)
+    }
+}
+
+/// Number written at a position of the spiral
+pub fn spiral_index(pos: IVec2) -> i64 {
+    let (x, y) = (pos.x, pos.y);
+    let k = x.abs().max(y.abs());
+    let m = (2 * k + 1).pow(2);
+
+    if y == -k {
+        m - (k - x)
+    } else if x == -k {
+        m - 2 * k - (k + y)
+    } else if y == k {
+        m - 4 * k - (k + x)
+    } else {
+        // x == k
+        m - 6 * k - (k - y)
+    }
+}
+
+/// Position of the number n on the spiral, n starts at 1
+pub fn spiral_position(n: i64) -> IVec2 {
+    assert!(n >= 1, "The spiral starts at 1");
+
+    // Ring k is the first whose bottom right corner (2k + 1)^2 reaches n
+    let mut side = n.isqrt();
+    if side * side < n {
+        side += 1;
+    }
+    let k = side / 2;
+
+    let m = (2 * k + 1).pow(2);
+    let t = 2 * k;
+    let d = m - n;
+
+    if k == 0 {
+        IVec2::new(0, 0)
+    } else if d < t {
+        IVec2::new(k - d, -k)
+    } else if d < 2 * t {
+        IVec2::new(-k, -k + (d - t))
+    } else if d < 3 * t {
+        IVec2::new(-k + (d - 2 * t), k)
+    } else {
+        IVec2::new(k, k - (d - 3 * t))
+    }
+}
+
+/// Directions on a hex grid with flat topped hexagons
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HexDir {
+    N,
+    NE,
+    SE,
+    S,
+    SW,
+    NW,
+}
+
+impl HexDir {
+    pub const ALL: [HexDir; 6] = [
+        HexDir::N,
+        HexDir::NE,
+        HexDir::SE,
+        HexDir::S,
+        HexDir::SW,
+        HexDir::NW,
+    ];
+
+    pub fn offset(&self) -> Hex {
+        match self {
+            HexDir::N => Hex::new(0, -1),
+            HexDir::NE => Hex::new(1, -1),
+            HexDir::SE => Hex::new(1, 0),
+            HexDir::S => Hex::new(0, 1),
+            HexDir::SW => Hex::new(-1, 1),
+            HexDir::NW => Hex::new(-1, 0),
+        }
+    }
+}
+
+impl std::str::FromStr for HexDir {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "n" => Ok(HexDir::N),
+            "ne" => Ok(HexDir::NE),
+            "se" => Ok(HexDir::SE),
+            "s" => Ok(HexDir::S),
+            "sw" => Ok(HexDir::SW),
+            "nw" => Ok(HexDir::NW),
+            _ => Err(format!("Invalid hex direction {}", s)),
+        }
+    }
+}
+
+/// Hex grid position in axial coordinates, the cube coordinate s is derived as -q - r
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Hex {
+    pub q: i64,
+    pub r: i64,
+}
+
+impl Hex {
+    pub fn new(q: i64, r: i64) -> Self {
+        Hex { q, r }
+    }
+
+    /// Builds a hex from cube coordinates, which must sum to zero
+    pub fn from_cube(q: i64, r: i64, s: i64) -> Self {
+        assert_eq!(q + r + s, 0, "Cube coordinates must sum to zero");
+        Hex { q, r }
+    }
+
+    pub fn s(&self) -> i64 {
+        -self.q - self.r
+    }
+
+    pub fn cube(&self) -> (i64, i64, i64) {
+        (self.q, self.r, self.s())
+    }
+
+    /// Number of steps from the origin
+    pub fn length(&self) -> i64 {
+        self.q.abs().max(self.r.abs()).max(self.s().abs())
+    }
+
+    pub fn distance(&self, other: Hex) -> i64 {
+        Hex::new(self.q - other.q, self.r - other.r).length()
+    }
+
+    pub fn step(&self, dir: HexDir) -> Hex {
+        *self + dir.offset()
+    }
+
+    pub fn neighbours(&self) -> [Hex; 6] {
+        HexDir::ALL.map(|d| self.step(d))
+    }
+}
+
+impl Add for Hex {
+    type Output = Hex;
+
+    fn add(self, rhs: Hex) -> Hex {
+        Hex::new(self.q + rhs.q, self.r + rhs.r)
+    }
+}
+
+impl Mul<i64> for Hex {
+    type Output = Hex;
+
+    fn mul(self, rhs: i64) -> Hex {
+        Hex::new(self.q * rhs, self.r * rhs)
+    }
+}
+
+/// Keypad where each line of the layout is a row, spaces are gaps without a key.
+/// Positions use x for the column and y for the row, growing downwards.
+#[derive(Debug, Clone)]
+pub struct Keypad {
+    layout: MatrixVec<char>,
+}
+
+impl Keypad {
+    pub fn new(layout: &str) -> Self {
+        let width = layout.lines().map(|l| l.chars().count()).max().unwrap_or(0);
+        let rows = layout
+            .lines()
+            .map(|l| {
+                let mut row = l.chars().collect::<Vec<_>>();
+                row.resize(width, ' ');
+                row
+            })
+            .collect::<Vec<_>>();
+
+        Keypad {
+            layout: MatrixVec::from_slices(&rows),
+        }
+    }
+
+    pub fn key(&self, pos: IVec2) -> Option<char> {
+        let row = usize::try_from(pos.y).ok()?;
+        let col = usize::try_from(pos.x).ok()?;
+        self.layout.get(row, col).copied().filter(|c| *c != ' ')
+    }
+
+    pub fn find(&self, key: char) -> Option<IVec2> {
+        let index = self.layout.iter().position(|c| *c == key)?;
+        let cols = self.layout.cols();
+        Some(IVec2::new((index % cols) as i64, (index / cols) as i64))
+    }
+
+    /// Moves one key in a direction, staying put if that would leave the pad
+    pub fn step(&self, pos: IVec2, dir: IVec2) -> IVec2 {
+        let next = pos + dir;
+        if self.key(next).is_some() { next } else { pos }
+    }
+}
+
+#[test]
+fn distances() {
+    let a = IVec2::new(1, -2);
+    let b = IVec2::new(-3, 5);
+    assert_eq!(manhattan(a, b), 11);
+    assert_eq!(chebyshev(a, b), 7);
+}
+
+#[test]
+fn spirals() {
+    for (n, pos) in (1..2000).zip(Spiral::new()) {
+        assert_eq!(spiral_position(n), pos);
+        assert_eq!(spiral_index(pos), n);
+    }
+
+    assert_eq!(manhattan(spiral_position(1024), IVec2::zeros()), 31);
+    let far = spiral_position(1_000_000_000_000_000);
+    assert_eq!(spiral_index(far), 1_000_000_000_000_000);
+}
+
+#[test]
+fn hex_grid() {
+    let walk = |path: &str| {
+        path.split(',')
+            .map(|s| s.parse::<HexDir>().unwrap())
+            .fold(Hex::default(), |h, d| h.step(d))
+    };
+
+    assert_eq!(walk("ne,ne,ne").length(), 3);
+    assert_eq!(walk("ne,ne,sw,sw").length(), 0);
+    assert_eq!(walk("ne,ne,s,s").length(), 2);
+    assert_eq!(walk("se,sw,se,sw,sw").length(), 3);
+
+    let h = Hex::from_cube(2, -1, -1);
+    assert!(h.neighbours().iter().all(|n| n.distance(h) == 1));
+    assert_eq!(h.distance(Hex::new(-1, 3)), 4);
+    assert_eq!(
+        HexDir::N.offset() * 3 + HexDir::S.offset() * 3,
+        Hex::default()
+    );
+}
+
+#[test]
+fn keypads() {
+    let pad = Keypad::new("  1\n 234\n56789\n ABC\n  D");
+    let start = pad.find('5').unwrap();
+    assert_eq!(pad.step(start, IVec2::new(-1, 0)), start);
+    assert_eq!(pad.key(pad.step(start, IVec2::new(1, 0))), Some('6'));
+    assert_eq!(pad.key(IVec2::new(0, 0)), None);
+    assert_eq!(pad.key(IVec2::new(-1, 2)), None);
+}
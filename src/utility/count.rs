@@ -0,0 +1,76 @@
+use std::fmt::{Debug, Display};
+
+use num::{BigInt, CheckedAdd, CheckedMul, FromPrimitive, One, ToPrimitive, Zero};
+
+/// Numbers that counting routines accumulate into. Primitive integers panic on
+/// overflow instead of wrapping, `BigInt` never overflows.
+pub trait Count: Clone + Debug + Zero + One + CheckedAdd + CheckedMul + FromPrimitive {
+    fn from_usize_checked(n: usize) -> Self {
+        Self::from_usize(n).expect("Count does not fit in the chosen type")
+    }
+
+    fn plus(&self, other: &Self) -> Self {
+        self.checked_add(other).expect("Count overflowed")
+    }
+
+    fn times(&self, other: &Self) -> Self {
+        self.checked_mul(other).expect("Count overflowed")
+    }
+}
+
+impl<T> Count for T where T: Clone + Debug + Zero + One + CheckedAdd + CheckedMul + FromPrimitive {}
+
+/// Puzzle answer that is kept as an `i64` while it fits and as a `BigInt` after
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Answer {
+    Small(i64),
+    Big(BigInt),
+}
+
+impl From<BigInt> for Answer {
+    fn from(value: BigInt) -> Self {
+        match value.to_i64() {
+            Some(v) => Answer::Small(v),
+            None => Answer::Big(value),
+        }
+    }
+}
+
+macro_rules! impl_answer_from {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(value: $t) -> Self {
+                    Answer::from(BigInt::from(value))
+                }
+            }
+        )*
+    };
+}
+
+impl_answer_from!(i32, i64, i128, u32, u64, u128, usize);
+
+impl Display for Answer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Answer::Small(v) => write!(f, "{}", v),
+            Answer::Big(v) => write!(f, "{}", v),
+        }
+    }
+}
+
+#[test]
+fn checked_counts() {
+    fn power<T: Count>(base: usize, exp: u32) -> T {
+        let base = T::from_usize_checked(base);
+        (0..exp).fold(T::one(), |acc, _| acc.times(&base))
+    }
+
+    assert_eq!(power::<u64>(2, 63), 1 << 63);
+    assert!(std::panic::catch_unwind(|| power::<u64>(2, 64)).is_err());
+
+    let big = power::<BigInt>(2, 64);
+    assert_eq!(Answer::from(big.clone()), Answer::Big(big));
+    assert_eq!(Answer::from(1u128 << 62), Answer::Small(1 << 62));
+    assert_eq!(Answer::from(u64::MAX).to_string(), "18446744073709551615");
+}
@@ -0,0 +1,98 @@
+/// Union-find over the elements 0..n with path compression and union by size
+#[derive(Debug, Clone)]
+pub struct DisjointSet {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+    components: usize,
+}
+
+impl DisjointSet {
+    pub fn new(n: usize) -> Self {
+        DisjointSet {
+            parent: (0..n).collect(),
+            size: vec![1; n],
+            components: n,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    /// Representative of the component holding x
+    pub fn find(&mut self, x: usize) -> usize {
+        let mut root = x;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+
+        let mut current = x;
+        while self.parent[current] != root {
+            let next = self.parent[current];
+            self.parent[current] = root;
+            current = next;
+        }
+
+        root
+    }
+
+    /// Joins the components of a and b, returns false if they were already joined
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+
+        if self.size[a] < self.size[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+        self.components -= 1;
+        true
+    }
+
+    pub fn same(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    pub fn component_count(&self) -> usize {
+        self.components
+    }
+
+    /// Size of the component holding x
+    pub fn size_of(&mut self, x: usize) -> usize {
+        let root = self.find(x);
+        self.size[root]
+    }
+
+    /// Sizes of every component, largest first
+    pub fn component_sizes(&self) -> Vec<usize> {
+        let mut sizes = (0..self.len())
+            .filter(|x| self.parent[*x] == *x)
+            .map(|x| self.size[x])
+            .collect::<Vec<_>>();
+        sizes.sort_by(|a, b| b.cmp(a));
+        sizes
+    }
+}
+
+#[test]
+fn components() {
+    let mut set = DisjointSet::new(8);
+    assert!(set.union(0, 1));
+    assert!(set.union(2, 3));
+    assert!(set.union(1, 3));
+    assert!(!set.union(0, 2));
+    assert!(set.union(5, 6));
+
+    assert!(set.same(0, 3));
+    assert!(!set.same(4, 5));
+    assert_eq!(set.size_of(2), 4);
+    assert_eq!(set.component_count(), 4);
+    assert_eq!(set.component_sizes(), vec![4, 2, 1, 1]);
+}
@@ -0,0 +1,505 @@
+/// Fixed length vector over GF(2), packed 64 bits per word
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BitVec {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl BitVec {
+    pub fn zeros(len: usize) -> Self {
+        BitVec {
+            words: vec![0; len.div_ceil(64)],
+            len,
+        }
+    }
+
+    pub fn from_bools(bits: &[bool]) -> Self {
+        let mut v = Self::zeros(bits.len());
+        for (i, _) in bits.iter().enumerate().filter(|(_, b)| **b) {
+            v.set(i, true);
+        }
+        v
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, i: usize) -> bool {
+        assert!(i < self.len);
+        self.words[i / 64] >> (i % 64) & 1 == 1
+    }
+
+    pub fn set(&mut self, i: usize, value: bool) {
+        assert!(i < self.len);
+        if value {
+            self.words[i / 64] |= 1 << (i % 64);
+        } else {
+            self.words[i / 64] &= !(1 << (i % 64));
+        }
+    }
+
+    pub fn flip(&mut self, i: usize) {
+        assert!(i < self.len);
+        self.words[i / 64] ^= 1 << (i % 64);
+    }
+
+    pub fn xor_assign(&mut self, other: &BitVec) {
+        debug_assert_eq!(self.len, other.len);
+        self.words
+            .iter_mut()
+            .zip(&other.words)
+            .for_each(|(a, b)| *a ^= b);
+    }
+
+    /// Hamming weight
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.words.iter().all(|w| *w == 0)
+    }
+
+    /// Indices of the set bits in increasing order
+    pub fn iter_ones(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, &w)| {
+            let mut word = w;
+            std::iter::from_fn(move || {
+                (word != 0).then(|| {
+                    let bit = word.trailing_zeros() as usize;
+                    word &= word - 1;
+                    i * 64 + bit
+                })
+            })
+        })
+    }
+}
+
+/// Matrix over GF(2) stored as packed rows, so eliminating a row is a few XORs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitMatrix {
+    rows: Vec<BitVec>,
+    cols: usize,
+}
+
+/// Every solution is `particular` xor any combination of the `kernel` vectors
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Solution {
+    pub particular: BitVec,
+    pub kernel: Vec<BitVec>,
+}
+
+impl BitMatrix {
+    pub fn new(rows: usize, cols: usize) -> Self {
+        BitMatrix {
+            rows: vec![BitVec::zeros(cols); rows],
+            cols,
+        }
+    }
+
+    pub fn from_rows(rows: Vec<BitVec>, cols: usize) -> Self {
+        assert!(rows.iter().all(|r| r.len() == cols));
+        BitMatrix { rows, cols }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> bool {
+        self.rows[row].get(col)
+    }
+
+    pub fn set(&mut self, row: usize, col: usize, value: bool) {
+        self.rows[row].set(col, value)
+    }
+
+    pub fn row(&self, row: usize) -> &BitVec {
+        &self.rows[row]
+    }
+
+    /// Matrix-vector product
+    pub fn multiply(&self, x: &BitVec) -> BitVec {
+        let mut out = BitVec::zeros(self.rows());
+        for (i, row) in self.rows.iter().enumerate() {
+            let parity = row
+                .words
+                .iter()
+                .zip(&x.words)
+                .fold(0, |acc, (a, b)| acc ^ (a & b).count_ones());
+            out.set(i, parity & 1 == 1);
+        }
+        out
+    }
+
+    /// Reduced row echelon form in place, only pivoting in the first `pivot_cols` columns.
+    /// Returns the pivot column of every non-zero row.
+    fn eliminate(&mut self, pivot_cols: usize) -> Vec<usize> {
+        let mut pivots = Vec::new();
+
+        for col in 0..pivot_cols {
+            let current = pivots.len();
+            let Some(pivot) = (current..self.rows()).find(|r| self.rows[*r].get(col)) else {
+                continue;
+            };
+            self.rows.swap(current, pivot);
+
+            let pivot_row = self.rows[current].clone();
+            for (r, row) in self.rows.iter_mut().enumerate() {
+                if r != current && row.get(col) {
+                    row.xor_assign(&pivot_row);
+                }
+            }
+
+            pivots.push(col);
+        }
+
+        pivots
+    }
+
+    pub fn reduced_row_echelon(&self) -> (BitMatrix, Vec<usize>) {
+        let mut m = self.clone();
+        let pivots = m.eliminate(self.cols);
+        (m, pivots)
+    }
+
+    pub fn rank(&self) -> usize {
+        self.reduced_row_echelon().1.len()
+    }
+
+    fn kernel_from(echelon: &BitMatrix, pivots: &[usize], cols: usize) -> Vec<BitVec> {
+        (0..cols)
+            .filter(|c| !pivots.contains(c))
+            .map(|free| {
+                let mut v = BitVec::zeros(cols);
+                v.set(free, true);
+                for (row, &pivot) in pivots.iter().enumerate() {
+                    if echelon.get(row, free) {
+                        v.set(pivot, true);
+                    }
+                }
+                v
+            })
+            .collect()
+    }
+
+    /// Basis of the vectors x with m * x = 0
+    pub fn kernel(&self) -> Vec<BitVec> {
+        let (echelon, pivots) = self.reduced_row_echelon();
+        Self::kernel_from(&echelon, &pivots, self.cols)
+    }
+
+    /// Solves m * x = b, returns None if there is no solution
+    pub fn solve(&self, b: &BitVec) -> Option<Solution> {
+        assert_eq!(b.len(), self.rows());
+
+        // Augment every row with its right hand side bit
+        let n = self.cols;
+        let rows = self
+            .rows
+            .iter()
+            .enumerate()
+            .map(|(i, row)| {
+                let mut augmented = BitVec::zeros(n + 1);
+                for c in row.iter_ones() {
+                    augmented.set(c, true);
+                }
+                augmented.set(n, b.get(i));
+                augmented
+            })
+            .collect();
+
+        let mut augmented = BitMatrix::from_rows(rows, n + 1);
+        let pivots = augmented.eliminate(n);
+
+        // Rows below the pivots are zero on the left, so a set bit on the right is 0 = 1
+        if augmented.rows[pivots.len()..].iter().any(|r| r.get(n)) {
+            return None;
+        }
+
+        let mut particular = BitVec::zeros(n);
+        for (row, &pivot) in pivots.iter().enumerate() {
+            particular.set(pivot, augmented.get(row, n));
+        }
+
+        Some(Solution {
+            particular,
+            kernel: Self::kernel_from(&augmented, &pivots, n),
+        })
+    }
+
+    /// Solution of m * x = b with the fewest set bits, exponential in the smaller of
+    /// the kernel dimension and the rank
+    pub fn min_weight_solution(&self, b: &BitVec) -> Result<BitVec, MinWeightError> {
+        let solution = self.solve(b).ok_or(MinWeightError::NoSolution)?;
+        min_weight_coset(&solution.particular, &solution.kernel)
+    }
+}
+
+/// Largest basis `min_weight_coset` walks, 2^30 XORs already take seconds
+pub const MAX_COSET_DIMENSION: usize = 30;
+
+/// Most positions outside the basis pivots `min_weight_coset` tabulates, one byte per
+/// combination of them
+pub const MAX_TABLE_BITS: usize = 24;
+
+/// Why no minimum weight solution was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MinWeightError {
+    NoSolution,
+    /// The basis spans this many dimensions, more than `MAX_COSET_DIMENSION`,
+    /// and the coset varies in more than `MAX_TABLE_BITS` other positions
+    TooLarge(usize),
+}
+
+impl std::fmt::Display for MinWeightError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MinWeightError::NoSolution => write!(f, "the system has no solution"),
+            MinWeightError::TooLarge(dimension) => write!(
+                f,
+                "searching a kernel of dimension {} needs 2^{} steps",
+                dimension, dimension
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MinWeightError {}
+
+/// Lowest weight vector in `start` xor span(basis).
+///
+/// Once the basis is in reduced echelon form, a vector of the coset is fixed by its bits
+/// at the k basis pivots, and its weight is those bits plus the r other positions it
+/// sets. Whichever side is smaller gets enumerated: the 2^k combinations of the basis
+/// in Gray code order, or a breadth first search over the 2^r patterns of the other
+/// positions. Fails when both are too large instead of running for hours.
+pub fn min_weight_coset(start: &BitVec, basis: &[BitVec]) -> Result<BitVec, MinWeightError> {
+    let (start, basis, others) = reduce(start, basis);
+
+    let (k, r) = (basis.len(), others.len());
+    if r < k && r <= MAX_TABLE_BITS {
+        Ok(search_table(&start, &basis, &others))
+    } else if k <= MAX_COSET_DIMENSION {
+        Ok(walk_span(&start, &basis))
+    } else {
+        Err(MinWeightError::TooLarge(k))
+    }
+}
+
+/// Reduced echelon form of the basis, `start` with the pivot bits cleared, and the
+/// positions other than the pivots that any of them sets
+fn reduce(start: &BitVec, basis: &[BitVec]) -> (BitVec, Vec<BitVec>, Vec<usize>) {
+    let n = start.len();
+    let (echelon, pivots) = BitMatrix::from_rows(basis.to_vec(), n).reduced_row_echelon();
+    let mut basis = echelon.rows;
+    basis.truncate(pivots.len());
+
+    let mut start = start.clone();
+    for (row, &pivot) in basis.iter().zip(&pivots) {
+        if start.get(pivot) {
+            start.xor_assign(row);
+        }
+    }
+
+    let others = (0..n)
+        .filter(|i| !pivots.contains(i) && basis.iter().chain([&start]).any(|v| v.get(*i)))
+        .collect();
+    (start, basis, others)
+}
+
+/// Every vector of the coset in Gray code order, so each step costs a single XOR
+fn walk_span(start: &BitVec, basis: &[BitVec]) -> BitVec {
+    let mut current = start.clone();
+    let mut best = current.clone();
+    let mut best_weight = current.count_ones();
+
+    for step in 1u64..1 << basis.len() {
+        current.xor_assign(&basis[step.trailing_zeros() as usize]);
+        let weight = current.count_ones();
+        if weight < best_weight {
+            best_weight = weight;
+            best = current.clone();
+        }
+    }
+
+    best
+}
+
+/// Breadth first search from the pattern `start` has at the `others` positions down to
+/// zero, where adding a basis vector or clearing one position each cost one set bit.
+/// Expects a reduced basis and a start that is clear at the pivots.
+fn search_table(start: &BitVec, basis: &[BitVec], others: &[usize]) -> BitVec {
+    let pattern = |v: &BitVec| {
+        others
+            .iter()
+            .enumerate()
+            .filter(|(_, i)| v.get(**i))
+            .fold(0usize, |acc, (bit, _)| acc | 1 << bit)
+    };
+    let moves: Vec<usize> = basis.iter().map(pattern).collect();
+
+    let origin = pattern(start);
+    let mut distance = vec![u8::MAX; 1 << others.len()];
+    distance[origin] = 0;
+    let mut queue = std::collections::VecDeque::from([origin]);
+
+    while distance[0] == u8::MAX {
+        let current = queue.pop_front().expect("Every pattern is reachable");
+        let next = distance[current] + 1;
+        let units = (0..others.len()).map(|bit| 1 << bit);
+        for step in moves.iter().copied().chain(units) {
+            if distance[current ^ step] == u8::MAX {
+                distance[current ^ step] = next;
+                queue.push_back(current ^ step);
+            }
+        }
+    }
+
+    // Walk back from zero, adding the basis vectors on a shortest path. The positions
+    // cleared along the way are exactly the ones left set in the result.
+    let mut best = start.clone();
+    let mut current = 0;
+    while current != origin {
+        let d = distance[current];
+        if let Some(i) = (0..moves.len()).find(|i| distance[current ^ moves[*i]] == d - 1) {
+            best.xor_assign(&basis[i]);
+            current ^= moves[i];
+        } else {
+            let bit = (0..others.len())
+                .find(|bit| distance[current ^ 1 << bit] == d - 1)
+                .expect("A shortest path has a previous step");
+            current ^= 1 << bit;
+        }
+    }
+
+    best
+}
+
+#[test]
+fn bit_vectors() {
+    let mut v = BitVec::zeros(130);
+    v.set(0, true);
+    v.set(64, true);
+    v.flip(129);
+    assert_eq!(v.iter_ones().collect::<Vec<_>>(), vec![0, 64, 129]);
+    assert_eq!(v.count_ones(), 3);
+
+    let w = v.clone();
+    v.xor_assign(&w);
+    assert!(v.is_zero());
+}
+
+#[test]
+fn elimination() {
+    let rows = [[1, 1, 0], [0, 1, 1], [1, 0, 1]]
+        .iter()
+        .map(|r| BitVec::from_bools(&r.map(|b| b == 1)))
+        .collect();
+    let m = BitMatrix::from_rows(rows, 3);
+
+    assert_eq!(m.rank(), 2);
+    let kernel = m.kernel();
+    assert_eq!(kernel.len(), 1);
+    assert!(m.multiply(&kernel[0]).is_zero());
+
+    assert!(
+        m.solve(&BitVec::from_bools(&[true, false, false]))
+            .is_none()
+    );
+    let solution = m.solve(&BitVec::from_bools(&[true, true, false])).unwrap();
+    assert_eq!(
+        m.multiply(&solution.particular),
+        BitVec::from_bools(&[true, true, false])
+    );
+}
+
+#[test]
+fn minimum_weight() {
+    // Lights toggled by 70 buttons, where button i toggles lights i and i + 1 (mod 70)
+    let n = 70;
+    let mut m = BitMatrix::new(n, n);
+    for i in 0..n {
+        m.set(i, i, true);
+        m.set((i + 1) % n, i, true);
+    }
+
+    let mut target = BitVec::zeros(n);
+    target.set(10, true);
+    target.set(15, true);
+
+    let best = m.min_weight_solution(&target).unwrap();
+    assert_eq!(m.multiply(&best), target);
+    assert_eq!(best.count_ones(), 5);
+
+    // Every button toggles two lights, so an odd number of lights cannot be reached
+    target.set(20, true);
+    assert_eq!(
+        m.min_weight_solution(&target),
+        Err(MinWeightError::NoSolution)
+    );
+
+    // A repeated basis vector spans nothing new
+    let basis = vec![BitVec::zeros(n); MAX_COSET_DIMENSION + 1];
+    assert_eq!(min_weight_coset(&target, &basis), Ok(target.clone()));
+
+    // 35 independent vectors each pairing light i with light i + 35
+    let basis: Vec<BitVec> = (0..35)
+        .map(|i| {
+            let mut v = BitVec::zeros(n);
+            v.set(i, true);
+            v.set(i + 35, true);
+            v
+        })
+        .collect();
+    assert_eq!(
+        min_weight_coset(&target, &basis),
+        Err(MinWeightError::TooLarge(35))
+    );
+}
+
+#[test]
+fn large_kernel() {
+    // 10 lights, 10 buttons toggling one light each and 40 toggling scrambled sets
+    let (rows, cols) = (10, 50);
+    let column = |c: usize| -> u64 {
+        if c < rows {
+            1 << c
+        } else {
+            (c as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15) >> 54
+        }
+    };
+    let mut m = BitMatrix::new(rows, cols);
+    for c in 0..cols {
+        for r in 0..rows {
+            m.set(r, c, column(c) >> r & 1 == 1);
+        }
+    }
+
+    let wanted = column(15) ^ column(27);
+    assert!((0..cols).all(|c| column(c) != wanted));
+    let target = BitVec::from_bools(&(0..rows).map(|r| wanted >> r & 1 == 1).collect::<Vec<_>>());
+
+    let solution = m.solve(&target).unwrap();
+    assert_eq!(solution.kernel.len(), 40);
+    let best = m.min_weight_solution(&target).unwrap();
+    assert_eq!(m.multiply(&best), target);
+    assert_eq!(best.count_ones(), 2);
+
+    // Both ways of enumerating agree where the span is small enough to walk
+    let (start, basis, others) = reduce(&solution.particular, &solution.kernel[..20]);
+    assert!(others.len() <= rows);
+    assert_eq!(
+        search_table(&start, &basis, &others).count_ones(),
+        walk_span(&start, &basis).count_ones()
+    );
+}
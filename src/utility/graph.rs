@@ -0,0 +1,401 @@
+use std::{collections::HashMap, hash::Hash};
+
+use crate::utility::count::Count;
+
+/// Directed graph whose nodes are interned labels, addressed by dense ids 0..len
+#[derive(Debug, Clone)]
+pub struct Graph<L> {
+    labels: Vec<L>,
+    ids: HashMap<L, usize>,
+    edges: Vec<Vec<usize>>,
+}
+
+impl<L: Hash + Eq + Clone> Default for Graph<L> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<L: Hash + Eq + Clone> Graph<L> {
+    pub fn new() -> Self {
+        Graph {
+            labels: Vec::new(),
+            ids: HashMap::new(),
+            edges: Vec::new(),
+        }
+    }
+
+    /// Id of the label, adding a node for it if it is new
+    pub fn intern(&mut self, label: L) -> usize {
+        if let Some(id) = self.ids.get(&label) {
+            return *id;
+        }
+        let id = self.labels.len();
+        self.ids.insert(label.clone(), id);
+        self.labels.push(label);
+        self.edges.push(Vec::new());
+        id
+    }
+
+    pub fn id(&self, label: &L) -> Option<usize> {
+        self.ids.get(label).copied()
+    }
+
+    pub fn label(&self, id: usize) -> &L {
+        &self.labels[id]
+    }
+
+    pub fn len(&self) -> usize {
+        self.labels.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+
+    pub fn add_edge(&mut self, from: usize, to: usize) {
+        self.edges[from].push(to);
+    }
+
+    /// Adds an edge between two labels, interning both
+    pub fn connect(&mut self, from: L, to: L) {
+        let (from, to) = (self.intern(from), self.intern(to));
+        self.add_edge(from, to);
+    }
+
+    pub fn neighbours(&self, id: usize) -> &[usize] {
+        &self.edges[id]
+    }
+
+    /// Same nodes and ids with every edge flipped
+    pub fn reversed(&self) -> Graph<L> {
+        let mut edges = vec![Vec::new(); self.len()];
+        for (from, targets) in self.edges.iter().enumerate() {
+            for to in targets {
+                edges[*to].push(from);
+            }
+        }
+        Graph {
+            labels: self.labels.clone(),
+            ids: self.ids.clone(),
+            edges,
+        }
+    }
+
+    /// Kahn's algorithm, None if the graph has a cycle
+    pub fn topological_sort(&self) -> Option<Vec<usize>> {
+        let mut in_degree = vec![0; self.len()];
+        for to in self.edges.iter().flatten() {
+            in_degree[*to] += 1;
+        }
+
+        let mut order = (0..self.len())
+            .filter(|n| in_degree[*n] == 0)
+            .collect::<Vec<_>>();
+        let mut next = 0;
+        while let Some(&node) = order.get(next) {
+            next += 1;
+            for to in &self.edges[node] {
+                in_degree[*to] -= 1;
+                if in_degree[*to] == 0 {
+                    order.push(*to);
+                }
+            }
+        }
+
+        (order.len() == self.len()).then_some(order)
+    }
+
+    pub fn has_cycle(&self) -> bool {
+        self.topological_sort().is_none()
+    }
+
+    /// Tarjan's algorithm without recursion. Components come out in reverse
+    /// topological order, a component is listed before any component that reaches it.
+    pub fn strongly_connected_components(&self) -> Vec<Vec<usize>> {
+        const UNVISITED: usize = usize::MAX;
+
+        let n = self.len();
+        let mut index = vec![UNVISITED; n];
+        let mut low = vec![0; n];
+        let mut on_stack = vec![false; n];
+        let mut stack = Vec::new();
+        let mut components = Vec::new();
+        let mut counter = 0;
+
+        for start in 0..n {
+            if index[start] != UNVISITED {
+                continue;
+            }
+
+            // Each frame is a node and the position of the next edge to follow
+            let mut frames = vec![(start, 0)];
+            index[start] = counter;
+            low[start] = counter;
+            counter += 1;
+            stack.push(start);
+            on_stack[start] = true;
+
+            while let Some((node, edge)) = frames.last_mut() {
+                let node = *node;
+                if let Some(&to) = self.edges[node].get(*edge) {
+                    *edge += 1;
+                    if index[to] == UNVISITED {
+                        index[to] = counter;
+                        low[to] = counter;
+                        counter += 1;
+                        stack.push(to);
+                        on_stack[to] = true;
+                        frames.push((to, 0));
+                    } else if on_stack[to] {
+                        low[node] = low[node].min(index[to]);
+                    }
+                    continue;
+                }
+
+                frames.pop();
+                if let Some((parent, _)) = frames.last() {
+                    low[*parent] = low[*parent].min(low[node]);
+                }
+
+                if low[node] == index[node] {
+                    let mut component = Vec::new();
+                    while let Some(member) = stack.pop() {
+                        on_stack[member] = false;
+                        component.push(member);
+                        if member == node {
+                            break;
+                        }
+                    }
+                    components.push(component);
+                }
+            }
+        }
+
+        components
+    }
+
+    /// Nodes reachable from root in reverse postorder
+    fn reverse_postorder(&self, root: usize) -> Vec<usize> {
+        let mut visited = vec![false; self.len()];
+        let mut order = Vec::new();
+        let mut frames = vec![(root, 0)];
+        visited[root] = true;
+
+        while let Some((node, edge)) = frames.last_mut() {
+            let node = *node;
+            if let Some(&to) = self.edges[node].get(*edge) {
+                *edge += 1;
+                if !visited[to] {
+                    visited[to] = true;
+                    frames.push((to, 0));
+                }
+            } else {
+                order.push(node);
+                frames.pop();
+            }
+        }
+
+        order.reverse();
+        order
+    }
+
+    /// Immediate dominator of every node reachable from root, with the iterative
+    /// algorithm of Cooper, Harvey and Kennedy. The root dominates itself.
+    pub fn dominators(&self, root: usize) -> Vec<Option<usize>> {
+        let order = self.reverse_postorder(root);
+        let mut position = vec![usize::MAX; self.len()];
+        for (i, node) in order.iter().enumerate() {
+            position[*node] = i;
+        }
+        let predecessors = self.reversed();
+
+        let mut idom = vec![None; self.len()];
+        idom[root] = Some(root);
+
+        let intersect = |idom: &[Option<usize>], mut a: usize, mut b: usize| {
+            while a != b {
+                while position[a] > position[b] {
+                    a = idom[a].unwrap();
+                }
+                while position[b] > position[a] {
+                    b = idom[b].unwrap();
+                }
+            }
+            a
+        };
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for &node in order.iter().skip(1) {
+                let new_idom = predecessors
+                    .neighbours(node)
+                    .iter()
+                    .filter(|p| idom[**p].is_some())
+                    .copied()
+                    .reduce(|a, b| intersect(&idom, a, b));
+
+                if new_idom.is_some() && idom[node] != new_idom {
+                    idom[node] = new_idom;
+                    changed = true;
+                }
+            }
+        }
+
+        idom
+    }
+
+    /// Number of paths from one node to another, None if there are infinitely many
+    pub fn count_paths<T: Count>(&self, from: usize, to: usize) -> Option<T> {
+        self.count_paths_through(from, to, &[])
+    }
+
+    /// Nodes on some path from one node to another in topological order, None if
+    /// one of them is on a cycle. Cycles elsewhere in the graph do not matter.
+    fn order_between(&self, from: usize, to: usize) -> Option<Vec<usize>> {
+        let mut between = vec![false; self.len()];
+        for node in self.reversed().reverse_postorder(to) {
+            between[node] = true;
+        }
+        let nodes = self
+            .reverse_postorder(from)
+            .into_iter()
+            .filter(|n| between[*n])
+            .collect::<Vec<_>>();
+
+        let mut in_degree = vec![0; self.len()];
+        for node in &nodes {
+            for to in self.edges[*node].iter().filter(|t| between[**t]) {
+                in_degree[*to] += 1;
+            }
+        }
+
+        let mut order = nodes
+            .iter()
+            .copied()
+            .filter(|n| in_degree[*n] == 0)
+            .collect::<Vec<_>>();
+        let mut next = 0;
+        while let Some(&node) = order.get(next) {
+            next += 1;
+            for to in self.edges[node].iter().filter(|t| between[**t]) {
+                in_degree[*to] -= 1;
+                if in_degree[*to] == 0 {
+                    order.push(*to);
+                }
+            }
+        }
+
+        (order.len() == nodes.len()).then_some(order)
+    }
+
+    /// Number of paths from one node to another that visit every node in `required`,
+    /// tracking the visited required nodes as a bit mask next to each node. None if a
+    /// cycle lies on a path between them, so the paths cannot be counted.
+    pub fn count_paths_through<T: Count>(
+        &self,
+        from: usize,
+        to: usize,
+        required: &[usize],
+    ) -> Option<T> {
+        let order = self.order_between(from, to)?;
+        let masks = 1 << required.len();
+        let bit = |node: usize| {
+            required
+                .iter()
+                .enumerate()
+                .filter(|(_, r)| **r == node)
+                .fold(0, |acc, (i, _)| acc | 1 << i)
+        };
+
+        let mut counts = vec![vec![T::zero(); masks]; self.len()];
+        counts[from][bit(from)] = T::one();
+
+        for &node in &order {
+            for mask in 0..masks {
+                if counts[node][mask].is_zero() {
+                    continue;
+                }
+                let current = counts[node][mask].clone();
+                for &next in &self.edges[node] {
+                    let next_mask = mask | bit(next);
+                    counts[next][next_mask] = counts[next][next_mask].plus(&current);
+                }
+            }
+        }
+
+        Some(counts[to][masks - 1].clone())
+    }
+}
+
+#[cfg(test)]
+fn diamond() -> Graph<&'static str> {
+    let mut graph = Graph::new();
+    for (a, b) in [
+        ("start", "a"),
+        ("start", "b"),
+        ("a", "c"),
+        ("b", "c"),
+        ("a", "b"),
+        ("c", "end"),
+    ] {
+        graph.connect(a, b);
+    }
+    graph
+}
+
+#[test]
+fn acyclic_graphs() {
+    let graph = diamond();
+    let id = |l| graph.id(&l).unwrap();
+
+    let order = graph.topological_sort().unwrap();
+    let position = |l| order.iter().position(|n| *n == id(l)).unwrap();
+    assert!(position("start") < position("a") && position("a") < position("b"));
+    assert!(!graph.has_cycle());
+
+    assert_eq!(graph.count_paths::<usize>(id("start"), id("end")), Some(3));
+    assert_eq!(
+        graph.count_paths_through::<usize>(id("start"), id("end"), &[id("a"), id("b")]),
+        Some(1)
+    );
+    assert_eq!(
+        graph
+            .reversed()
+            .count_paths::<usize>(id("end"), id("start")),
+        Some(3)
+    );
+
+    let idom = graph.dominators(id("start"));
+    assert_eq!(idom[id("c")], Some(id("start")));
+    assert_eq!(idom[id("end")], Some(id("c")));
+}
+
+#[test]
+fn cyclic_graphs() {
+    let mut graph = Graph::new();
+    for (a, b) in [(0, 1), (1, 2), (2, 0), (2, 3), (3, 4), (4, 3), (5, 4)] {
+        graph.connect(a, b);
+    }
+    assert!(graph.has_cycle());
+
+    let label = |c: &Vec<usize>| {
+        let mut l = c.iter().map(|n| *graph.label(*n)).collect::<Vec<_>>();
+        l.sort();
+        l
+    };
+    let components = graph.strongly_connected_components();
+    let labelled = components.iter().map(label).collect::<Vec<_>>();
+    assert_eq!(labelled, vec![vec![3, 4], vec![0, 1, 2], vec![5]]);
+
+    // Only cycles between the two nodes make the count infinite
+    for (a, b) in [(6, 5), (6, 7), (7, 8), (6, 8)] {
+        graph.connect(a, b);
+    }
+    let id = |l| graph.id(&l).unwrap();
+    assert_eq!(graph.count_paths::<usize>(id(6), id(8)), Some(2));
+    assert_eq!(graph.count_paths::<usize>(id(8), id(6)), Some(0));
+    assert_eq!(graph.count_paths::<usize>(id(6), id(3)), None);
+    assert_eq!(graph.count_paths::<usize>(id(0), id(3)), None);
+}
@@ -1,6 +1,20 @@
+use std::{
+    collections::BTreeMap,
+    panic::AssertUnwindSafe,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        mpsc::{self, Receiver},
+    },
+    thread::JoinHandle,
+};
+
+use md5::{Digest, Md5};
+
+pub mod stream;
+
 pub fn md5_hash(str: &[u8]) -> [u8; 16] {
-    use md5::Digest;
-    md5::Md5::digest(str).into()
+    Md5::digest(str).into()
 }
 
 pub fn md5_to_hex(hash: &[u8; 16]) -> [u8; 32] {
@@ -19,3 +33,261 @@ pub fn md5_to_hex(hash: &[u8; 16]) -> [u8; 32] {
 
     hex
 }
+
+/// The i-th hex digit of the hash, 0 is the most significant
+pub fn nibble(hash: &[u8; 16], i: usize) -> u8 {
+    if i.is_multiple_of(2) {
+        hash[i / 2] >> 4
+    } else {
+        hash[i / 2] & 0x0F
+    }
+}
+
+/// Number of zero hex digits the hash starts with
+pub fn leading_zero_nibbles(hash: &[u8; 16]) -> usize {
+    (0..32).take_while(|i| nibble(hash, *i) == 0).count()
+}
+
+/// Decimal digits of n written to the end of the buffer, without allocating
+fn format_index(mut n: u64, buffer: &mut [u8; 20]) -> &[u8] {
+    let mut start = buffer.len();
+    loop {
+        start -= 1;
+        buffer[start] = b'0' + (n % 10) as u8;
+        n /= 10;
+        if n == 0 {
+            return &buffer[start..];
+        }
+    }
+}
+
+/// Rehashes the lowercase hex form of the hash `rounds` times
+pub fn stretch(mut hash: [u8; 16], rounds: usize) -> [u8; 16] {
+    for _ in 0..rounds {
+        hash = md5_hash(&md5_to_hex(&hash));
+    }
+    hash
+}
+
+/// Hashes `salt + index` for increasing indices, spread over threads in chunks of indices
+#[derive(Debug, Clone)]
+pub struct Miner {
+    salt: Vec<u8>,
+    threads: usize,
+    chunk_size: u64,
+    rounds: usize,
+}
+
+impl Miner {
+    pub fn new(salt: &[u8]) -> Self {
+        Miner {
+            salt: salt.to_vec(),
+            threads: std::thread::available_parallelism().map_or(1, |n| n.get()),
+            chunk_size: 4096,
+            rounds: 0,
+        }
+    }
+
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    pub fn chunk_size(mut self, chunk_size: u64) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+
+    /// Extra rounds of hashing the hex digest, for key stretching
+    pub fn stretch(mut self, rounds: usize) -> Self {
+        self.rounds = rounds;
+        self
+    }
+
+    /// Hash of a single index
+    pub fn hash(&self, index: u64) -> [u8; 16] {
+        let mut buffer = [0; 20];
+        let hash = Md5::new_with_prefix(&self.salt)
+            .chain_update(format_index(index, &mut buffer))
+            .finalize()
+            .into();
+        stretch(hash, self.rounds)
+    }
+
+    /// Every (index, hash) accepted by the filter, in index order. The threads stop
+    /// when the iterator is dropped, and a panic in the filter is raised again by it.
+    pub fn mine<F>(&self, accept: F) -> Mined
+    where
+        F: Fn(&[u8; 16]) -> bool + Send + Sync + 'static,
+    {
+        self.mine_from(0, accept)
+    }
+
+    /// Like `mine` but skipping the indices before `start`
+    pub fn mine_from<F>(&self, start: u64, accept: F) -> Mined
+    where
+        F: Fn(&[u8; 16]) -> bool + Send + Sync + 'static,
+    {
+        let accept = Arc::new(accept);
+        let next_chunk = Arc::new(AtomicU64::new(0));
+        let stop = Arc::new(AtomicBool::new(false));
+        // Bounded so the threads cannot run far ahead of the reader
+        let (sender, receiver) = mpsc::sync_channel(2 * self.threads);
+
+        let workers = (0..self.threads)
+            .map(|_| {
+                let (accept, next_chunk, stop) = (accept.clone(), next_chunk.clone(), stop.clone());
+                let sender = sender.clone();
+                let base = Md5::new_with_prefix(&self.salt);
+                let (chunk_size, rounds) = (self.chunk_size, self.rounds);
+
+                std::thread::spawn(move || {
+                    let mut buffer = [0; 20];
+                    let mined = std::panic::catch_unwind(AssertUnwindSafe(|| {
+                        while !stop.load(Ordering::Relaxed) {
+                            let chunk = next_chunk.fetch_add(1, Ordering::Relaxed);
+                            // Chunks past the last index are never sent, so the reader runs dry
+                            let Some(first) = chunk
+                                .checked_mul(chunk_size)
+                                .and_then(|offset| start.checked_add(offset))
+                            else {
+                                break;
+                            };
+                            let last = first.saturating_add(chunk_size - 1);
+                            let found = (first..=last)
+                                .filter_map(|index| {
+                                    let hash = base
+                                        .clone()
+                                        .chain_update(format_index(index, &mut buffer))
+                                        .finalize()
+                                        .into();
+                                    let hash = stretch(hash, rounds);
+                                    accept(&hash).then_some((index, hash))
+                                })
+                                .collect::<Vec<_>>();
+                            if sender.send(Ok((chunk, found))).is_err() {
+                                break;
+                            }
+                        }
+                    }));
+
+                    // Hand the panic to the reader, which would otherwise wait for the lost chunk
+                    if let Err(panic) = mined {
+                        let message = panic
+                            .downcast_ref::<&str>()
+                            .map(|m| m.to_string())
+                            .or_else(|| panic.downcast_ref::<String>().cloned())
+                            .unwrap_or_default();
+                        let _ = sender.send(Err(message));
+                    }
+                })
+            })
+            .collect();
+
+        Mined {
+            receiver: Some(receiver),
+            workers,
+            stop,
+            pending: BTreeMap::new(),
+            next_chunk: 0,
+            current: Vec::new().into_iter(),
+        }
+    }
+
+    /// Hashes whose hex form starts with the given number of zeros
+    pub fn leading_zeros(&self, zeros: usize) -> Mined {
+        self.mine(move |hash| leading_zero_nibbles(hash) >= zeros)
+    }
+}
+
+/// A chunk number and what was found in it, or the message of a thread that panicked
+type Chunk = Result<(u64, Vec<(u64, [u8; 16])>), String>;
+
+/// Results of `Miner::mine`, chunks are put back in order as they come in from the threads
+pub struct Mined {
+    receiver: Option<Receiver<Chunk>>,
+    workers: Vec<JoinHandle<()>>,
+    stop: Arc<AtomicBool>,
+    pending: BTreeMap<u64, Vec<(u64, [u8; 16])>>,
+    next_chunk: u64,
+    current: std::vec::IntoIter<(u64, [u8; 16])>,
+}
+
+impl Iterator for Mined {
+    type Item = (u64, [u8; 16]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(found) = self.current.next() {
+                return Some(found);
+            }
+
+            while !self.pending.contains_key(&self.next_chunk) {
+                match self.receiver.as_ref()?.recv().ok()? {
+                    Ok((chunk, found)) => self.pending.insert(chunk, found),
+                    Err(message) => panic!("Mining thread panicked: {}", message),
+                };
+            }
+            self.current = self.pending.remove(&self.next_chunk).unwrap().into_iter();
+            self.next_chunk += 1;
+        }
+    }
+}
+
+impl Drop for Mined {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        // Unblocks any thread waiting to send
+        self.receiver = None;
+        for worker in self.workers.drain(..) {
+            worker.join().unwrap();
+        }
+    }
+}
+
+#[test]
+fn hash_helpers() {
+    let mut buffer = [0; 20];
+    assert_eq!(format_index(0, &mut buffer), b"0");
+    assert_eq!(format_index(3231929, &mut buffer), b"3231929");
+    assert_eq!(format_index(u64::MAX, &mut buffer), b"18446744073709551615");
+
+    let hash = md5_hash(b"abc3231929");
+    assert_eq!(&md5_to_hex(&hash)[..7], b"0000015");
+    assert_eq!(leading_zero_nibbles(&hash), 5);
+    assert_eq!(nibble(&hash, 5), 1);
+    assert_eq!(nibble(&hash, 6), 5);
+    assert_eq!(Miner::new(b"abc").hash(3231929), hash);
+}
+
+#[test]
+fn mining_in_order() {
+    let expected = (0..20000u64)
+        .map(|i| (i, md5_hash(format!("xyz{}", i).as_bytes())))
+        .filter(|(_, h)| h[0] == 0)
+        .collect::<Vec<_>>();
+
+    for threads in [1, 4] {
+        let miner = Miner::new(b"xyz").threads(threads).chunk_size(97);
+        let found = miner
+            .mine(|h| h[0] == 0)
+            .take(expected.len())
+            .collect::<Vec<_>>();
+        assert_eq!(found, expected);
+    }
+}
+
+#[test]
+fn mining_edges() {
+    // The last indices are mined once, without wrapping around
+    let miner = Miner::new(b"xyz").threads(2).chunk_size(4);
+    let last = miner
+        .mine_from(u64::MAX - 5, |_| true)
+        .map(|(i, _)| i)
+        .collect::<Vec<_>>();
+    assert_eq!(last, (u64::MAX - 5..=u64::MAX).collect::<Vec<_>>());
+
+    // A panic in the filter reaches the reader instead of leaving it waiting
+    let failing = miner.mine(|hash| hash[0] == 0 && panic!("bad hash"));
+    assert!(std::panic::catch_unwind(AssertUnwindSafe(|| failing.count())).is_err());
+}
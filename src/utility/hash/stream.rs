@@ -0,0 +1,257 @@
+use std::{
+    collections::VecDeque,
+    fs::{File, OpenOptions},
+    io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+use super::{Mined, Miner, nibble};
+
+/// A hash with the runs of equal hex digits found in it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Runs {
+    pub hash: [u8; 16],
+    /// Digit of the first run of three
+    pub triple: Option<u8>,
+    /// Bit d is set when digit d appears five times in a row
+    pub quintuples: u16,
+}
+
+impl Runs {
+    pub fn new(hash: [u8; 16]) -> Self {
+        let digits: [u8; 32] = std::array::from_fn(|i| nibble(&hash, i));
+        let same = |w: &[u8]| w.iter().all(|d| *d == w[0]);
+
+        Runs {
+            hash,
+            triple: digits.windows(3).find(|w| same(w)).map(|w| w[0]),
+            quintuples: digits
+                .windows(5)
+                .filter(|w| same(w))
+                .fold(0, |acc, w| acc | 1 << w[0]),
+        }
+    }
+
+    pub fn has_quintuple(&self, digit: u8) -> bool {
+        self.quintuples & (1 << digit) != 0
+    }
+}
+
+/// Cached hashes recomputed when a cache file is opened, spread evenly over the file
+const CACHE_SAMPLES: u64 = 16;
+
+/// Hashes of `salt + index` in index order, optionally stretched, read back from a cache
+/// file where earlier runs left them and mined in parallel past that
+pub struct HashStream {
+    miner: Miner,
+    next_index: u64,
+    cached: Option<BufReader<File>>,
+    writer: Option<BufWriter<File>>,
+    mined: Option<Mined>,
+}
+
+impl HashStream {
+    pub fn new(salt: &[u8], rounds: usize) -> Self {
+        // Stretched hashes are slow, smaller chunks get the first ones out sooner
+        let chunk_size = if rounds > 0 { 32 } else { 4096 };
+        HashStream {
+            miner: Miner::new(salt).stretch(rounds).chunk_size(chunk_size),
+            next_index: 0,
+            cached: None,
+            writer: None,
+            mined: None,
+        }
+    }
+
+    /// Keeps the raw hashes in `dir`, in one file per salt and round count. A file where
+    /// any of `CACHE_SAMPLES` hashes spread over it is wrong is started over.
+    pub fn with_cache_dir(mut self, dir: impl AsRef<Path>) -> io::Result<Self> {
+        let salt = self
+            .miner
+            .salt
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>();
+        std::fs::create_dir_all(&dir)?;
+        let path = dir
+            .as_ref()
+            .join(format!("{}-{}.md5", salt, self.miner.rounds));
+
+        let writer = OpenOptions::new().create(true).append(true).open(&path)?;
+        // Drop a hash that was only partly written
+        let count = writer.metadata()?.len() / 16;
+        writer.set_len(count * 16)?;
+
+        let mut reader = File::open(&path)?;
+        let last = count.saturating_sub(1);
+        for index in (0..CACHE_SAMPLES).map(|i| i * last / (CACHE_SAMPLES - 1)) {
+            if count > 0 && read_hash(&mut reader, index)? != self.miner.hash(index) {
+                writer.set_len(0)?;
+                break;
+            }
+        }
+        reader.seek(SeekFrom::Start(0))?;
+
+        self.cached = Some(BufReader::new(reader));
+        self.writer = Some(BufWriter::new(writer));
+        Ok(self)
+    }
+}
+
+fn read_hash(file: &mut File, index: u64) -> io::Result<[u8; 16]> {
+    let mut hash = [0; 16];
+    file.seek(SeekFrom::Start(index * 16))?;
+    file.read_exact(&mut hash)?;
+    Ok(hash)
+}
+
+impl Iterator for HashStream {
+    type Item = (u64, [u8; 16]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(reader) = &mut self.cached {
+            let mut hash = [0; 16];
+            if reader.read_exact(&mut hash).is_ok() {
+                self.next_index += 1;
+                return Some((self.next_index - 1, hash));
+            }
+            self.cached = None;
+        }
+
+        let (miner, start) = (&self.miner, self.next_index);
+        let mined = self
+            .mined
+            .get_or_insert_with(|| miner.mine_from(start, |_| true));
+        let (index, hash) = mined.next()?;
+
+        if let Some(writer) = &mut self.writer
+            && writer.write_all(&hash).is_err()
+        {
+            self.writer = None;
+        }
+        self.next_index = index + 1;
+        Some((index, hash))
+    }
+}
+
+/// Indices of one-time pad keys: hashes with a run of three whose digit shows up five
+/// in a row in one of the next `lookahead` hashes. Only that window of hashes is kept.
+pub struct Keys {
+    stream: HashStream,
+    lookahead: usize,
+    window: VecDeque<(u64, Runs)>,
+    /// Hashes in the window with a run of five of each digit
+    quintuples: [usize; 16],
+}
+
+pub fn keys(stream: HashStream, lookahead: usize) -> Keys {
+    Keys {
+        stream,
+        lookahead,
+        window: VecDeque::with_capacity(lookahead + 1),
+        quintuples: [0; 16],
+    }
+}
+
+impl Keys {
+    fn count(&mut self, runs: &Runs, delta: isize) {
+        for digit in 0..16 {
+            if runs.has_quintuple(digit) {
+                self.quintuples[digit as usize] = self.quintuples[digit as usize]
+                    .checked_add_signed(delta)
+                    .unwrap();
+            }
+        }
+    }
+}
+
+impl Iterator for Keys {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            while self.window.len() <= self.lookahead {
+                let (index, hash) = self.stream.next()?;
+                let runs = Runs::new(hash);
+                self.count(&runs, 1);
+                self.window.push_back((index, runs));
+            }
+
+            let (index, runs) = self.window.pop_front().unwrap();
+            self.count(&runs, -1);
+            if let Some(digit) = runs.triple
+                && self.quintuples[digit as usize] > 0
+            {
+                return Some(index);
+            }
+        }
+    }
+}
+
+#[test]
+fn runs() {
+    let mut hash = [0x12; 16];
+    hash[3] = 0x88;
+    hash[4] = 0x8f;
+    hash[10] = 0xaa;
+    hash[11] = 0xaa;
+    hash[12] = 0xa3;
+
+    let runs = Runs::new(hash);
+    assert_eq!(runs.triple, Some(8));
+    assert!(runs.has_quintuple(0xa));
+    assert!(!runs.has_quintuple(8));
+}
+
+#[test]
+fn one_time_pad() {
+    let mut found = keys(HashStream::new(b"abc", 0), 1000);
+    assert_eq!(found.next(), Some(39));
+    assert_eq!(found.next(), Some(92));
+    assert_eq!(found.nth(61), Some(22728));
+
+    let stretched = Miner::new(b"abc").stretch(2016).hash(0);
+    assert_eq!(&super::md5_to_hex(&stretched)[..6], b"a107ff");
+}
+
+#[test]
+fn cache_file() {
+    let dir = std::env::temp_dir().join(format!("hash-stream-{}", std::process::id()));
+    let first = |stream: HashStream| stream.take(500).collect::<Vec<_>>();
+
+    let fresh = first(HashStream::new(b"abc", 1).with_cache_dir(&dir).unwrap());
+    let file = dir.join("616263-1.md5");
+    assert_eq!(std::fs::metadata(&file).unwrap().len() % 16, 0);
+    assert!(std::fs::metadata(&file).unwrap().len() >= 500 * 16);
+
+    // A torn write at the end is dropped and recomputed
+    let mut torn = OpenOptions::new().append(true).open(&file).unwrap();
+    torn.write_all(&[1, 2, 3]).unwrap();
+    let length = std::fs::metadata(&file).unwrap().len();
+
+    let cached = HashStream::new(b"abc", 1).with_cache_dir(&dir).unwrap();
+    let reread = cached.take(length as usize / 16 + 10).collect::<Vec<_>>();
+    assert_eq!(reread[..500], fresh[..]);
+    assert_eq!(
+        reread.last().unwrap().1,
+        Miner::new(b"abc").stretch(1).hash(reread.len() as u64 - 1)
+    );
+
+    // Wrong hashes are noticed between a right first and last one
+    let hashes = std::fs::metadata(&file).unwrap().len() as usize / 16;
+    let mut middle = OpenOptions::new().write(true).open(&file).unwrap();
+    middle.seek(SeekFrom::Start(16)).unwrap();
+    middle.write_all(&vec![7; (hashes - 2) * 16]).unwrap();
+    drop(middle);
+    let patched = HashStream::new(b"abc", 1).with_cache_dir(&dir).unwrap();
+    assert_eq!(first(patched)[..], fresh[..]);
+    assert_eq!(std::fs::metadata(&file).unwrap().len(), 500 * 16);
+
+    // A file with the wrong hashes in it is thrown away
+    std::fs::write(&file, [7; 64 * 16]).unwrap();
+    let stale = HashStream::new(b"abc", 1).with_cache_dir(&dir).unwrap();
+    assert_eq!(first(stale)[..], fresh[..]);
+    assert_eq!(std::fs::metadata(&file).unwrap().len(), 500 * 16);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,481 @@
+use num::{BigInt, BigRational, Integer, One, Signed, Zero};
+
+type Q = BigRational;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Relation {
+    LessEq,
+    GreaterEq,
+    Equal,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// Proven optimal values of the variables, with the objective value
+    Optimal {
+        value: i64,
+        values: Vec<i64>,
+    },
+    Infeasible,
+    /// Some integer point is feasible and the objective has no limit
+    Unbounded,
+    /// The search gave up after visiting this many branch and bound nodes
+    NodeLimit(usize),
+}
+
+/// Branch and bound nodes visited before `solve` gives up, by default
+const NODE_LIMIT: usize = 100_000;
+
+#[derive(Debug, Clone)]
+struct Constraint {
+    coefficients: Vec<i64>,
+    relation: Relation,
+    rhs: i64,
+}
+
+/// Integer linear program: optimise a linear objective over integer variables
+/// subject to linear constraints. Variables default to the bounds `0..=inf`.
+#[derive(Debug, Clone)]
+pub struct Problem {
+    objective: Vec<i64>,
+    maximize: bool,
+    constraints: Vec<Constraint>,
+    lower: Vec<i64>,
+    upper: Vec<Option<i64>>,
+    node_limit: usize,
+}
+
+/// Outcome of the relaxation at one branch and bound node
+enum Relaxation {
+    Optimal { value: Q, values: Vec<Q> },
+    Infeasible,
+    Unbounded,
+}
+
+fn q(v: i64) -> Q {
+    Q::from_integer(BigInt::from(v))
+}
+
+fn to_i64(v: &Q) -> i64 {
+    v.to_integer()
+        .try_into()
+        .expect("Value does not fit in i64")
+}
+
+impl Problem {
+    pub fn minimize(objective: &[i64]) -> Self {
+        let n = objective.len();
+        Problem {
+            objective: objective.to_vec(),
+            maximize: false,
+            constraints: Vec::new(),
+            lower: vec![0; n],
+            upper: vec![None; n],
+            node_limit: NODE_LIMIT,
+        }
+    }
+
+    pub fn maximize(objective: &[i64]) -> Self {
+        let mut problem = Self::minimize(&objective.iter().map(|c| -c).collect::<Vec<_>>());
+        problem.maximize = true;
+        problem
+    }
+
+    pub fn constraint(mut self, coefficients: &[i64], relation: Relation, rhs: i64) -> Self {
+        assert_eq!(coefficients.len(), self.objective.len());
+        self.constraints.push(Constraint {
+            coefficients: coefficients.to_vec(),
+            relation,
+            rhs,
+        });
+        self
+    }
+
+    /// Restricts a variable to `lower..=upper`, with no upper limit if `upper` is None
+    pub fn bounds(mut self, variable: usize, lower: i64, upper: Option<i64>) -> Self {
+        self.lower[variable] = lower;
+        self.upper[variable] = upper;
+        self
+    }
+
+    /// Most branch and bound nodes `solve` visits before returning `Outcome::NodeLimit`
+    pub fn node_limit(mut self, limit: usize) -> Self {
+        self.node_limit = limit;
+        self
+    }
+
+    /// An equality whose coefficients share a factor that does not divide the right
+    /// hand side has no integer solution, however far the search branches
+    fn has_indivisible_equality(&self) -> bool {
+        self.constraints
+            .iter()
+            .filter(|c| c.relation == Relation::Equal)
+            .any(|c| {
+                let g = c.coefficients.iter().fold(0, |g: i64, a| g.gcd(a));
+                if g == 0 { c.rhs != 0 } else { c.rhs % g != 0 }
+            })
+    }
+
+    /// Solves the problem exactly with branch and bound over the LP relaxation
+    pub fn solve(&self) -> Outcome {
+        if self.has_indivisible_equality() {
+            return Outcome::Infeasible;
+        }
+
+        let mut best: Option<(i64, Vec<i64>)> = None;
+        let mut stack = vec![(self.lower.clone(), self.upper.clone())];
+        let mut nodes = 0;
+
+        while let Some((lower, upper)) = stack.pop() {
+            nodes += 1;
+            if nodes > self.node_limit {
+                return Outcome::NodeLimit(self.node_limit);
+            }
+
+            let (value, values) = match self.relaxation(&lower, &upper) {
+                Relaxation::Infeasible => continue,
+                Relaxation::Unbounded => return self.unbounded_if_feasible(),
+                Relaxation::Optimal { value, values } => (value, values),
+            };
+
+            // Integer objective coefficients give an integer objective value
+            let bound = to_i64(&value.ceil());
+            if best.as_ref().is_some_and(|(b, _)| bound >= *b) {
+                continue;
+            }
+
+            match values.iter().position(|v| !v.is_integer()) {
+                None => {
+                    best = Some((bound, values.iter().map(to_i64).collect()));
+                }
+                Some(i) => {
+                    let floor = to_i64(&values[i].floor());
+
+                    let mut down = upper.clone();
+                    down[i] = Some(floor);
+                    let mut up = lower.clone();
+                    up[i] = floor + 1;
+
+                    stack.push((lower.clone(), down));
+                    stack.push((up, upper));
+                }
+            }
+        }
+
+        match best {
+            Some((value, values)) => Outcome::Optimal {
+                value: if self.maximize { -value } else { value },
+                values,
+            },
+            None => Outcome::Infeasible,
+        }
+    }
+
+    /// With rational data an unbounded relaxation makes the integer program unbounded
+    /// as soon as it has any integer point, so look for one with a zero objective
+    fn unbounded_if_feasible(&self) -> Outcome {
+        let feasibility = Problem {
+            objective: vec![0; self.objective.len()],
+            maximize: false,
+            ..self.clone()
+        };
+        match feasibility.solve() {
+            Outcome::Optimal { .. } => Outcome::Unbounded,
+            other => other,
+        }
+    }
+
+    /// LP relaxation with the given bounds, solved with the two phase simplex method.
+    /// Variables are shifted by their lower bound so they are all non-negative.
+    fn relaxation(&self, lower: &[i64], upper: &[Option<i64>]) -> Relaxation {
+        let n = self.objective.len();
+        if (0..n).any(|i| upper[i].is_some_and(|u| u < lower[i])) {
+            return Relaxation::Infeasible;
+        }
+
+        let mut rows: Vec<(Vec<Q>, Relation, Q)> = self
+            .constraints
+            .iter()
+            .map(|c| {
+                let shift: i64 = c.coefficients.iter().zip(lower).map(|(a, l)| a * l).sum();
+                (
+                    c.coefficients.iter().map(|a| q(*a)).collect(),
+                    c.relation,
+                    q(c.rhs - shift),
+                )
+            })
+            .collect();
+
+        for (i, u) in upper.iter().enumerate() {
+            if let Some(u) = u {
+                let mut coefficients = vec![Q::zero(); n];
+                coefficients[i] = Q::one();
+                rows.push((coefficients, Relation::LessEq, q(u - lower[i])));
+            }
+        }
+
+        // Standard form: one slack column per inequality and non-negative right hand sides
+        let slacks = rows.iter().filter(|r| r.1 != Relation::Equal).count();
+        let mut slack = n;
+        let mut a = Vec::with_capacity(rows.len());
+        let mut b = Vec::with_capacity(rows.len());
+
+        for (coefficients, relation, rhs) in rows {
+            let mut row = coefficients;
+            row.resize(n + slacks, Q::zero());
+            match relation {
+                Relation::LessEq => row[slack] = Q::one(),
+                Relation::GreaterEq => row[slack] = -Q::one(),
+                Relation::Equal => (),
+            }
+            if relation != Relation::Equal {
+                slack += 1;
+            }
+
+            if rhs.is_negative() {
+                row.iter_mut().for_each(|v| *v = -v.clone());
+                b.push(-rhs);
+            } else {
+                b.push(rhs);
+            }
+            a.push(row);
+        }
+
+        let mut c: Vec<Q> = self.objective.iter().map(|v| q(*v)).collect();
+        c.resize(n + slacks, Q::zero());
+
+        let offset: i64 = self.objective.iter().zip(lower).map(|(c, l)| c * l).sum();
+
+        match simplex(a, b, &c) {
+            Relaxation::Optimal { value, mut values } => {
+                values.truncate(n);
+                values.iter_mut().zip(lower).for_each(|(v, l)| *v += q(*l));
+                Relaxation::Optimal {
+                    value: value + q(offset),
+                    values,
+                }
+            }
+            other => other,
+        }
+    }
+}
+
+/// Dense simplex tableau, the last column holds the right hand side
+struct Tableau {
+    rows: Vec<Vec<Q>>,
+    basis: Vec<usize>,
+}
+
+impl Tableau {
+    fn pivot(&mut self, row: usize, col: usize) {
+        let lead = self.rows[row][col].clone();
+        self.rows[row].iter_mut().for_each(|v| *v /= lead.clone());
+
+        let pivot_row = self.rows[row].clone();
+        for (r, other) in self.rows.iter_mut().enumerate() {
+            let factor = other[col].clone();
+            if r != row && !factor.is_zero() {
+                other
+                    .iter_mut()
+                    .zip(&pivot_row)
+                    .for_each(|(v, p)| *v -= factor.clone() * p);
+            }
+        }
+        self.basis[row] = col;
+    }
+
+    /// Minimises `cost` over the first `columns` columns, using Bland's rule so it never cycles.
+    /// Returns false if the objective is unbounded.
+    fn optimise(&mut self, cost: &[Q], columns: usize) -> bool {
+        loop {
+            let reduced = |j: usize| {
+                self.rows
+                    .iter()
+                    .zip(&self.basis)
+                    .fold(cost[j].clone(), |acc, (row, b)| {
+                        acc - cost[*b].clone() * &row[j]
+                    })
+            };
+
+            let Some(entering) = (0..columns).find(|j| reduced(*j).is_negative()) else {
+                return true;
+            };
+
+            let leaving = self
+                .rows
+                .iter()
+                .enumerate()
+                .filter(|(_, row)| row[entering].is_positive())
+                .map(|(i, row)| {
+                    (
+                        row[row.len() - 1].clone() / &row[entering],
+                        self.basis[i],
+                        i,
+                    )
+                })
+                .min();
+
+            match leaving {
+                Some((_, _, row)) => self.pivot(row, entering),
+                None => return false,
+            }
+        }
+    }
+
+    fn value(&self, cost: &[Q]) -> Q {
+        let rhs = self.rows.first().map_or(0, |r| r.len() - 1);
+        self.rows
+            .iter()
+            .zip(&self.basis)
+            .fold(Q::zero(), |acc, (row, b)| {
+                acc + cost[*b].clone() * &row[rhs]
+            })
+    }
+}
+
+/// Minimises c.x subject to a.x = b and x >= 0, where b >= 0
+fn simplex(a: Vec<Vec<Q>>, b: Vec<Q>, c: &[Q]) -> Relaxation {
+    let m = a.len();
+    let n = c.len();
+
+    // Phase one: one artificial variable per row, minimise their sum
+    let rows = a
+        .into_iter()
+        .zip(b)
+        .enumerate()
+        .map(|(i, (mut row, rhs))| {
+            row.extend((0..m).map(|j| if i == j { Q::one() } else { Q::zero() }));
+            row.push(rhs);
+            row
+        })
+        .collect();
+
+    let mut tableau = Tableau {
+        rows,
+        basis: (n..n + m).collect(),
+    };
+
+    let mut phase_one = vec![Q::zero(); n];
+    phase_one.extend(std::iter::repeat_n(Q::one(), m));
+    tableau.optimise(&phase_one, n + m);
+
+    if tableau.value(&phase_one).is_positive() {
+        return Relaxation::Infeasible;
+    }
+
+    // Drive the remaining artificial variables out, dropping redundant rows
+    let mut i = 0;
+    while i < tableau.rows.len() {
+        if tableau.basis[i] >= n {
+            match (0..n).find(|j| !tableau.rows[i][*j].is_zero()) {
+                Some(j) => tableau.pivot(i, j),
+                None => {
+                    tableau.rows.remove(i);
+                    tableau.basis.remove(i);
+                    continue;
+                }
+            }
+        }
+        i += 1;
+    }
+
+    let mut cost = c.to_vec();
+    cost.extend(std::iter::repeat_n(Q::zero(), m));
+
+    if !tableau.optimise(&cost, n) {
+        return Relaxation::Unbounded;
+    }
+
+    let rhs = n + m;
+    let mut values = vec![Q::zero(); n];
+    for (row, b) in tableau.rows.iter().zip(&tableau.basis) {
+        values[*b] = row[rhs].clone();
+    }
+
+    Relaxation::Optimal {
+        value: tableau.value(&cost),
+        values,
+    }
+}
+
+#[test]
+fn knapsack() {
+    // Maximise 5x + 4y + 3z with 2x + 3y + z <= 5, 4x + y + 2z <= 11, 3x + 4y + 2z <= 8
+    let problem = Problem::maximize(&[5, 4, 3])
+        .constraint(&[2, 3, 1], Relation::LessEq, 5)
+        .constraint(&[4, 1, 2], Relation::LessEq, 11)
+        .constraint(&[3, 4, 2], Relation::LessEq, 8);
+
+    assert_eq!(
+        problem.solve(),
+        Outcome::Optimal {
+            value: 13,
+            values: vec![2, 0, 1]
+        }
+    );
+}
+
+#[test]
+fn integrality_gap() {
+    // The relaxation reaches 3.5 at x = 3.5, but only even sums are reachable
+    let problem = Problem::maximize(&[1, 1])
+        .constraint(&[2, 2], Relation::LessEq, 7)
+        .constraint(&[1, -1], Relation::Equal, 0);
+    assert_eq!(
+        problem.solve(),
+        Outcome::Optimal {
+            value: 2,
+            values: vec![1, 1]
+        }
+    );
+
+    let problem = Problem::minimize(&[1]).constraint(&[2], Relation::Equal, 3);
+    assert_eq!(problem.solve(), Outcome::Infeasible);
+
+    let problem = Problem::maximize(&[1, 0]).constraint(&[0, 1], Relation::LessEq, 3);
+    assert_eq!(problem.solve(), Outcome::Unbounded);
+}
+
+#[test]
+fn no_integer_points() {
+    // 2x - 2y is always even, the relaxation alone would branch on y forever
+    let problem = Problem::minimize(&[1, 1]).constraint(&[2, -2], Relation::Equal, 1);
+    assert_eq!(problem.solve(), Outcome::Infeasible);
+    let problem = Problem::maximize(&[1, 1]).constraint(&[2, -2], Relation::Equal, 1);
+    assert_eq!(problem.solve(), Outcome::Infeasible);
+
+    // The same line written as two inequalities is not caught up front, so the cap ends it
+    let problem = Problem::maximize(&[1, 1])
+        .constraint(&[2, -2], Relation::LessEq, 1)
+        .constraint(&[2, -2], Relation::GreaterEq, 1)
+        .node_limit(50);
+    assert_eq!(problem.solve(), Outcome::NodeLimit(50));
+}
+
+#[test]
+fn bounded_variables() {
+    // Minimise presses to reach 10 with buttons adding 3 and 4, each pressed at most twice
+    let problem = Problem::minimize(&[1, 1])
+        .constraint(&[3, 4], Relation::Equal, 10)
+        .bounds(0, 0, Some(2))
+        .bounds(1, 0, Some(2));
+    assert_eq!(
+        problem.solve(),
+        Outcome::Optimal {
+            value: 3,
+            values: vec![2, 1]
+        }
+    );
+
+    let problem = Problem::minimize(&[1, 1])
+        .constraint(&[1, 1], Relation::GreaterEq, -5)
+        .bounds(0, -3, Some(4))
+        .bounds(1, -1, None);
+    assert_eq!(
+        problem.solve(),
+        Outcome::Optimal {
+            value: -4,
+            values: vec![-3, -1]
+        }
+    );
+}
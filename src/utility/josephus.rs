@@ -0,0 +1,186 @@
+//! Elimination games on a circle of players numbered 1..=n, where player 1 moves first
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rule {
+    /// Count k players starting from the current one and eliminate the last,
+    /// k = 2 eliminates the player to the left
+    Step(usize),
+    /// Eliminate the player directly across, the nearer one when there are two
+    Across,
+}
+
+/// Zero based survivor of the step k game, built up one player at a time from
+/// J(m + 1) = (J(m) + k) mod (m + 1). While the sum does not wrap, many players are
+/// added in one go, so this takes O(k log n) iterations and constant memory.
+fn step_survivor(n: usize, k: usize) -> usize {
+    if k == 1 {
+        return n - 1;
+    }
+
+    let (mut m, mut j) = (1, 0);
+    while m < n {
+        // Players that can be added before j + k reaches the circle size
+        let t = ((m - 1 - j) / (k - 1)).min(n - m);
+        if t > 0 {
+            j += t * k;
+            m += t;
+        } else {
+            m += 1;
+            let r = k % m;
+            j = if j >= m - r { j - (m - r) } else { j + r };
+        }
+    }
+    j
+}
+
+/// Largest power of 3 below n, stopping before the multiplication can overflow
+fn power_of_3_below(n: usize) -> usize {
+    let mut v = 1usize;
+
+    while let Some(next) = v.checked_mul(3)
+        && next < n
+    {
+        v = next;
+    }
+
+    v
+}
+
+/// Last remaining player
+pub fn winner(n: usize, rule: Rule) -> usize {
+    assert!(n > 0, "The game needs at least one player");
+
+    match rule {
+        Rule::Step(0) => panic!("Step must be at least 1"),
+        Rule::Step(2) => {
+            let diff = n - (1 << n.ilog2());
+            diff * 2 + 1
+        }
+        Rule::Step(k) => step_survivor(n, k) + 1,
+        Rule::Across if n == 1 => 1,
+        Rule::Across => {
+            let min = power_of_3_below(n);
+            let diff = n - min;
+
+            if diff <= min {
+                diff
+            } else {
+                diff + (diff - min)
+            }
+        }
+    }
+}
+
+/// Fenwick tree over the remaining players that finds the i-th one in O(log n)
+struct RankTree {
+    tree: Vec<usize>,
+}
+
+impl RankTree {
+    fn full(n: usize) -> Self {
+        let mut tree = vec![0; n + 1];
+        for i in 1..=n {
+            tree[i] += 1;
+            let parent = i + (i & i.wrapping_neg());
+            if parent <= n {
+                tree[parent] += tree[i];
+            }
+        }
+        RankTree { tree }
+    }
+
+    /// Removes and returns the player with the given zero based rank
+    fn remove_rank(&mut self, rank: usize) -> usize {
+        let n = self.tree.len() - 1;
+        let mut pos = 0;
+        let mut remaining = rank;
+
+        let mut step = if n == 0 { 0 } else { 1 << n.ilog2() };
+        while step > 0 {
+            if pos + step <= n && self.tree[pos + step] <= remaining {
+                pos += step;
+                remaining -= self.tree[pos];
+            }
+            step >>= 1;
+        }
+
+        let player = pos + 1;
+        let mut i = player;
+        while i <= n {
+            self.tree[i] -= 1;
+            i += i & i.wrapping_neg();
+        }
+        player
+    }
+}
+
+/// Every player in the order they are eliminated by simulating the game, the winner is last
+pub fn elimination_order(n: usize, rule: Rule) -> Vec<usize> {
+    assert!(n > 0, "The game needs at least one player");
+
+    let mut players = RankTree::full(n);
+    let mut order = Vec::with_capacity(n);
+    let mut current = 0;
+
+    for remaining in (2..=n).rev() {
+        let victim = match rule {
+            Rule::Step(0) => panic!("Step must be at least 1"),
+            Rule::Step(k) => (current + k - 1) % remaining,
+            Rule::Across => (current + remaining / 2) % remaining,
+        };
+        order.push(players.remove_rank(victim));
+
+        current = match rule {
+            // Counting restarts from the player after the one eliminated
+            Rule::Step(_) => victim % (remaining - 1),
+            Rule::Across if victim < current => current % (remaining - 1),
+            Rule::Across => (current + 1) % (remaining - 1),
+        };
+    }
+
+    order.push(players.remove_rank(0));
+    order
+}
+
+#[test]
+fn small_games() {
+    assert_eq!(elimination_order(5, Rule::Step(2)), vec![2, 4, 1, 5, 3]);
+    assert_eq!(
+        elimination_order(7, Rule::Step(3)),
+        vec![3, 6, 2, 7, 5, 1, 4]
+    );
+    assert_eq!(elimination_order(5, Rule::Across), vec![3, 5, 1, 4, 2]);
+    assert_eq!(winner(5, Rule::Across), 2);
+}
+
+#[test]
+fn formulas_match_simulation() {
+    for n in 1..200 {
+        for rule in [Rule::Across, Rule::Step(1), Rule::Step(2), Rule::Step(3)]
+            .into_iter()
+            .chain((5..300).step_by(37).map(Rule::Step))
+        {
+            let simulated = *elimination_order(n, rule).last().unwrap();
+            assert_eq!(winner(n, rule), simulated, "{} players, {:?}", n, rule);
+        }
+    }
+}
+
+#[test]
+fn large_circles() {
+    assert_eq!(winner(usize::MAX, Rule::Step(2)), usize::MAX);
+    assert_eq!(winner(3, Rule::Across), 3);
+    assert_eq!(
+        winner(usize::MAX, Rule::Across),
+        usize::MAX - 3usize.pow(40)
+    );
+
+    let simulated = *elimination_order(100_000, Rule::Step(7)).last().unwrap();
+    assert_eq!(winner(100_000, Rule::Step(7)), simulated);
+
+    // Removing a lap at a time would recurse about k ln(n / k) deep here
+    assert_eq!(
+        winner(1_000_000_000_000_000_000, Rule::Step(1_000_000)),
+        285_939_544_668_917_008
+    );
+}
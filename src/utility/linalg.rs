@@ -0,0 +1,264 @@
+use std::ops::Neg;
+
+use num::Num;
+
+use crate::utility::matrix::MatrixVec;
+
+/// Elements that can be added, multiplied and divided exactly,
+/// e.g. `Rational64` or `ModInt<P>` for a prime P (GF(2) is `ModInt<2>`)
+pub trait Field: Num + Clone + Default + Neg<Output = Self> {}
+
+impl<T> Field for T where T: Num + Clone + Default + Neg<Output = T> {}
+
+/// Matrix in reduced row echelon form
+#[derive(Debug, Clone)]
+pub struct Echelon<T> {
+    pub matrix: MatrixVec<T>,
+    /// Pivot column of each non-zero row, in order
+    pub pivots: Vec<usize>,
+    /// Product of the pivots and row swaps, the determinant when the matrix is square and full rank
+    scale: T,
+}
+
+impl<T> Echelon<T> {
+    pub fn rank(&self) -> usize {
+        self.pivots.len()
+    }
+}
+
+/// Gauss-Jordan elimination, only picking pivots in the first `pivot_cols` columns
+fn eliminate<T: Field>(mut m: MatrixVec<T>, pivot_cols: usize) -> Echelon<T> {
+    let rows = m.rows();
+    let mut pivots = Vec::new();
+    let mut scale = T::one();
+
+    for col in 0..pivot_cols {
+        let current = pivots.len();
+        if current == rows {
+            break;
+        }
+
+        let Some(pivot) = (current..rows).find(|r| !m.get(*r, col).unwrap().is_zero()) else {
+            continue;
+        };
+
+        if pivot != current {
+            m.swap_rows(pivot, current);
+            scale = -scale;
+        }
+
+        let lead = m.get(current, col).unwrap().clone();
+        scale = scale * lead.clone();
+        m.row_iter_mut(current)
+            .for_each(|v| *v = v.clone() / lead.clone());
+
+        let pivot_row = m.get_row(current).unwrap().to_vec();
+        for r in (0..rows).filter(|r| *r != current) {
+            let factor = m.get(r, col).unwrap().clone();
+            if !factor.is_zero() {
+                m.row_iter_mut(r)
+                    .zip(&pivot_row)
+                    .for_each(|(v, p)| *v = v.clone() - factor.clone() * p.clone());
+            }
+        }
+
+        pivots.push(col);
+    }
+
+    Echelon {
+        matrix: m,
+        pivots,
+        scale,
+    }
+}
+
+pub fn reduced_row_echelon<T: Field>(m: MatrixVec<T>) -> Echelon<T> {
+    let cols = m.cols();
+    eliminate(m, cols)
+}
+
+pub fn rank<T: Field>(m: &MatrixVec<T>) -> usize {
+    reduced_row_echelon(m.clone()).rank()
+}
+
+pub fn determinant<T: Field>(m: &MatrixVec<T>) -> T {
+    assert_eq!(m.rows(), m.cols(), "Determinant needs a square matrix");
+    let echelon = reduced_row_echelon(m.clone());
+    if echelon.rank() == m.rows() {
+        echelon.scale
+    } else {
+        T::zero()
+    }
+}
+
+pub fn inverse<T: Field>(m: &MatrixVec<T>) -> Option<MatrixVec<T>> {
+    assert_eq!(m.rows(), m.cols(), "Inverse needs a square matrix");
+    let n = m.rows();
+
+    let mut augmented = MatrixVec::new(n, 2 * n);
+    for (r, row) in m.iter_rows().enumerate() {
+        for (c, v) in row.iter().enumerate() {
+            augmented.set(r, c, v.clone());
+        }
+        augmented.set(r, n + r, T::one());
+    }
+
+    let echelon = eliminate(augmented, n);
+    if echelon.rank() < n {
+        return None;
+    }
+
+    let data = echelon
+        .matrix
+        .iter_rows()
+        .flat_map(|row| row[n..].to_vec())
+        .collect();
+    Some(MatrixVec::from_vec(n, n, data))
+}
+
+/// Basis of the kernel read from an echelon form, one vector per free column
+/// with that column set to one
+fn kernel_basis<T: Field>(echelon: &Echelon<T>, cols: usize) -> Vec<Vec<T>> {
+    (0..cols)
+        .filter(|c| !echelon.pivots.contains(c))
+        .map(|free| {
+            let mut v = vec![T::zero(); cols];
+            v[free] = T::one();
+            for (row, &pivot) in echelon.pivots.iter().enumerate() {
+                v[pivot] = -echelon.matrix.get(row, free).unwrap().clone();
+            }
+            v
+        })
+        .collect()
+}
+
+/// Basis of the vectors x with m * x = 0
+pub fn null_space<T: Field>(m: &MatrixVec<T>) -> Vec<Vec<T>> {
+    kernel_basis(&reduced_row_echelon(m.clone()), m.cols())
+}
+
+/// Every solution of a linear system is `particular` plus a combination of `null_space`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Solution<T> {
+    /// The solution with every free variable set to zero
+    pub particular: Vec<T>,
+    pub null_space: Vec<Vec<T>>,
+}
+
+/// Solves a * x = b, returns None if the system is inconsistent
+pub fn solve<T: Field>(a: &MatrixVec<T>, b: &[T]) -> Option<Solution<T>> {
+    assert_eq!(
+        a.rows(),
+        b.len(),
+        "Right hand side does not match the matrix"
+    );
+    let n = a.cols();
+
+    let data = a
+        .iter_rows()
+        .zip(b)
+        .flat_map(|(row, v)| {
+            row.iter()
+                .chain(std::iter::once(v))
+                .cloned()
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    let augmented = MatrixVec::from_vec(a.rows(), n + 1, data);
+
+    let echelon = eliminate(augmented, n);
+    let inconsistent =
+        (echelon.rank()..a.rows()).any(|r| !echelon.matrix.get(r, n).unwrap().is_zero());
+    if inconsistent {
+        return None;
+    }
+
+    let mut particular = vec![T::zero(); n];
+    for (row, &pivot) in echelon.pivots.iter().enumerate() {
+        particular[pivot] = echelon.matrix.get(row, n).unwrap().clone();
+    }
+
+    Some(Solution {
+        particular,
+        null_space: kernel_basis(&echelon, n),
+    })
+}
+
+/// Matrix-vector product
+pub fn multiply<T: Field>(m: &MatrixVec<T>, x: &[T]) -> Vec<T> {
+    m.iter_rows()
+        .map(|row| {
+            row.iter()
+                .zip(x)
+                .fold(T::zero(), |acc, (a, b)| acc + a.clone() * b.clone())
+        })
+        .collect()
+}
+
+fn rational_matrix(rows: usize, cols: usize, values: &[i64]) -> MatrixVec<num::Rational64> {
+    MatrixVec::from_vec(rows, cols, values.to_vec()).map(|v| num::Rational64::from(*v))
+}
+
+#[test]
+fn determinant_and_inverse() {
+    use num::Rational64;
+
+    let m = rational_matrix(3, 3, &[1, 2, 3, 0, 1, 4, 5, 6, 0]);
+    assert_eq!(determinant(&m), Rational64::from(1));
+
+    let inv = inverse(&m).unwrap();
+    assert_eq!(
+        inv,
+        rational_matrix(3, 3, &[-24, 18, 5, 20, -15, -4, -5, 4, 1])
+    );
+
+    let m = rational_matrix(2, 2, &[0, 2, 3, 0]);
+    assert_eq!(determinant(&m), Rational64::from(-6));
+
+    let singular = rational_matrix(2, 2, &[1, 2, 2, 4]);
+    assert_eq!(determinant(&singular), Rational64::from(0));
+    assert_eq!(inverse(&singular), None);
+    assert_eq!(rank(&singular), 1);
+}
+
+#[test]
+fn rational_system() {
+    use num::Rational64;
+
+    // x + y + z = 6, y - z = 1
+    let a = rational_matrix(2, 3, &[1, 1, 1, 0, 1, -1]);
+    let b = [6, 1].map(Rational64::from);
+    let solution = solve(&a, &b).unwrap();
+
+    assert_eq!(multiply(&a, &solution.particular), b.to_vec());
+    assert_eq!(solution.null_space.len(), 1);
+    assert_eq!(
+        multiply(&a, &solution.null_space[0]),
+        vec![Rational64::from(0); 2]
+    );
+
+    let inconsistent = rational_matrix(2, 2, &[1, 1, 1, 1]);
+    assert_eq!(solve(&inconsistent, &[1, 2].map(Rational64::from)), None);
+}
+
+#[test]
+fn finite_fields() {
+    use crate::utility::modular::ModInt;
+    type GF2 = ModInt<2>;
+    type GF7 = ModInt<7>;
+
+    // Over GF(2) the rows sum to zero, so the rank drops
+    let m = MatrixVec::from_vec(3, 3, vec![1, 1, 0, 0, 1, 1, 1, 0, 1]).map(|v| GF2::new(*v));
+    assert_eq!(rank(&m), 2);
+    let kernel = null_space(&m);
+    assert_eq!(kernel, vec![vec![GF2::new(1); 3]]);
+
+    let m = MatrixVec::from_vec(2, 2, vec![3, 5, 1, 4]).map(|v| GF7::new(*v));
+    assert_eq!(determinant(&m), GF7::new(0));
+    let m = MatrixVec::from_vec(2, 2, vec![3, 5, 1, 2]).map(|v| GF7::new(*v));
+    let inv = inverse(&m).unwrap();
+    assert_eq!(
+        multiply(&inv, &multiply(&m, &[GF7::new(4), GF7::new(6)])),
+        vec![GF7::new(4), GF7::new(6)]
+    );
+}
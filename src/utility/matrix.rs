@@ -123,6 +123,11 @@ impl<T> MatrixVec<T> {
         MatrixVec { rows, cols, data }
     }
 
+    /// Creates a new matrix of the same shape by applying `f` to every element
+    pub fn map<U, F: FnMut(&T) -> U>(&self, f: F) -> MatrixVec<U> {
+        MatrixVec::from_vec(self.rows, self.cols, self.data.iter().map(f).collect())
+    }
+
     /// Mutable iterator over all elements in row-major order
     pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
         self.data.iter_mut()
@@ -1,12 +1,28 @@
 pub mod assembunny;
+pub mod boxes;
+pub mod coords;
+pub mod count;
 pub mod directions;
+pub mod dsu;
+pub mod gf2;
+pub mod graph;
 pub mod hash;
+pub mod ilp;
 pub mod input;
+pub mod josephus;
+pub mod linalg;
 pub mod matrix;
 pub mod modular;
 pub mod parsing;
+pub mod polygon;
+pub mod primes;
 pub mod ranges;
+pub mod sequence;
+pub mod spatial;
 pub mod tree;
+pub mod tsp;
+pub mod vfs;
+pub mod vm;
 
 #[macro_export]
 macro_rules! check_result {
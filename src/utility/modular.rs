@@ -1,5 +1,6 @@
 use std::ops::AddAssign;
 
+use num::Integer;
 use num::Num;
 use num::Signed;
 
@@ -52,3 +53,425 @@ pub fn get_divisors<N: Num + Ord + AddAssign + Copy>(n: N) -> Vec<N> {
     v.sort();
     v
 }
+
+/// a * b mod m by doubling, so nothing larger than 2m is ever formed.
+/// Works for any integer type, where `mul_mod` needs a wider one.
+fn mul_mod_doubling<T>(a: T, b: T, m: &T) -> T
+where
+    T: Integer + Clone,
+{
+    let two = T::one() + T::one();
+    let (mut a, mut b) = (a.mod_floor(m), b.mod_floor(m));
+    let mut product = T::zero();
+    while !b.is_zero() {
+        if b.is_odd() {
+            product = (product + a.clone()).mod_floor(m);
+        }
+        a = (a.clone() + a).mod_floor(m);
+        b = b / two.clone();
+    }
+    product
+}
+
+/// Solves the system x = r_i (mod m_i) by merging the congruences pairwise.
+/// Moduli don't need to be coprime. Returns (x, lcm of moduli) with 0 <= x < lcm,
+/// or None if the congruences contradict each other or a modulus is not positive.
+/// Intermediate values stay below the lcm, so T only has to hold the answer.
+pub fn crt<T>(congruences: &[(T, T)]) -> Option<(T, T)>
+where
+    T: Integer + Signed + Clone,
+{
+    congruences
+        .iter()
+        .try_fold((T::zero(), T::one()), |(r1, m1), (r2, m2)| {
+            if !m2.is_positive() {
+                return None;
+            }
+
+            let e = m1.extended_gcd(m2);
+            let diff = r2.clone() - r1.clone();
+            if !diff.is_multiple_of(&e.gcd) {
+                return None;
+            }
+
+            let step = m2.clone() / e.gcd.clone();
+            let k = mul_mod_doubling(diff / e.gcd, e.x, &step);
+            let lcm = m1.clone() * step;
+
+            Some(((r1 + m1 * k).mod_floor(&lcm), lcm))
+        })
+}
+
+#[test]
+fn crt_coprime() {
+    assert_eq!(crt(&[(2i64, 3), (3, 5), (2, 7)]), Some((23, 105)));
+    assert_eq!(crt::<i64>(&[]), Some((0, 1)));
+}
+
+#[test]
+fn crt_non_coprime() {
+    assert_eq!(crt(&[(2i64, 4), (4, 6)]), Some((10, 12)));
+    assert_eq!(crt(&[(1i64, 4), (2, 6)]), None);
+    assert_eq!(crt(&[(-1i64, 6), (3, 4)]), Some((11, 12)));
+    assert_eq!(crt(&[(1i64, 4), (2, 0)]), None);
+    assert_eq!(crt(&[(1i64, -4)]), None);
+}
+
+#[test]
+fn crt_near_i64_limit() {
+    // The lcm only just fits in i64, but multiplying before reducing would not
+    let (a, b) = (1_073_741_789i64, 8_589_934_583i64);
+    let x = a * b - 1;
+    assert_eq!(crt(&[(x % a, a), (x % b, b)]), Some((x, a * b)));
+    assert_eq!(crt(&[(x % b, b), (x % a, a)]), Some((x, a * b)));
+    assert_eq!(mul_mod_doubling(-3i64, b - 1, &b), 3);
+}
+
+#[test]
+fn crt_large() {
+    // Product of the moduli is around 10^36, far beyond i64
+    let primes: [i128; 4] = [1_000_000_007, 998_244_353, 1_000_000_009, 754_974_721];
+    let x: i128 = 123_456_789_012_345_678_901_234_567;
+    let congruences: Vec<_> = primes.iter().map(|p| (x % p, *p)).collect();
+
+    let (solution, modulus) = crt(&congruences).unwrap();
+    assert_eq!(solution, x % modulus);
+
+    let big: Vec<_> = congruences
+        .iter()
+        .map(|(r, m)| (num::BigInt::from(*r), num::BigInt::from(*m)))
+        .collect();
+    assert_eq!(crt(&big).unwrap().0, num::BigInt::from(solution));
+}
+
+/// a * b mod m without overflowing, for any modulus that fits in u64
+pub fn mul_mod(a: u64, b: u64, m: u64) -> u64 {
+    ((a as u128 * b as u128) % m as u128) as u64
+}
+
+/// base^exp mod m by repeated squaring
+pub fn pow_mod(mut base: u64, mut exp: u64, m: u64) -> u64 {
+    let mut result = 1 % m;
+    base %= m;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = mul_mod(result, base, m);
+        }
+        base = mul_mod(base, base, m);
+        exp >>= 1;
+    }
+    result
+}
+
+fn reduce(value: i128, m: u64) -> u64 {
+    value.rem_euclid(m as i128) as u64
+}
+
+/// Integer modulo the compile time constant M. These form a ring, and a field only
+/// when M is prime: elsewhere division panics on divisors sharing a factor with M.
+/// `ModInt<0>` fails to compile as soon as a value is made.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, PartialOrd, Ord)]
+pub struct ModInt<const M: u64>(u64);
+
+impl<const M: u64> ModInt<M> {
+    pub fn new(value: i64) -> Self {
+        Self::reduced(value as i128)
+    }
+
+    fn reduced(value: i128) -> Self {
+        const { assert!(M > 0, "The modulus must be positive") }
+        ModInt(reduce(value, M))
+    }
+
+    pub fn value(&self) -> u64 {
+        self.0
+    }
+
+    pub fn modulus(&self) -> u64 {
+        M
+    }
+
+    fn with(&self, value: u64) -> Self {
+        ModInt(value)
+    }
+}
+
+/// Integer modulo a modulus chosen at runtime. Unlike `ModInt` it has no `num` traits,
+/// as `Zero::zero()`, `One::one()` and `Num::from_str_radix` cannot know the modulus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Mod {
+    value: u64,
+    modulus: u64,
+}
+
+impl Mod {
+    pub fn new(value: i64, modulus: u64) -> Self {
+        assert!(modulus > 0, "Modulus must be positive");
+        Mod {
+            value: reduce(value as i128, modulus),
+            modulus,
+        }
+    }
+
+    pub fn value(&self) -> u64 {
+        self.value
+    }
+
+    pub fn modulus(&self) -> u64 {
+        self.modulus
+    }
+
+    fn with(&self, value: u64) -> Self {
+        Mod {
+            value,
+            modulus: self.modulus,
+        }
+    }
+}
+
+macro_rules! impl_mod_ops {
+    ([$($generics:tt)*] $t:ty) => {
+        impl<$($generics)*> $t {
+            /// self^exp by repeated squaring
+            pub fn pow(&self, exp: u64) -> Self {
+                self.with(pow_mod(self.value(), exp, self.modulus()))
+            }
+
+            /// Multiplicative inverse, if the value is coprime with the modulus
+            pub fn inverse(&self) -> Option<Self> {
+                modular_inverse(self.value() as i128, self.modulus() as i128)
+                    .map(|x| self.with(x as u64))
+            }
+        }
+
+        impl<$($generics)*> std::ops::Add for $t {
+            type Output = Self;
+            fn add(self, rhs: Self) -> Self {
+                debug_assert_eq!(self.modulus(), rhs.modulus());
+                let sum = self.value() as u128 + rhs.value() as u128;
+                self.with((sum % self.modulus() as u128) as u64)
+            }
+        }
+
+        impl<$($generics)*> std::ops::Sub for $t {
+            type Output = Self;
+            fn sub(self, rhs: Self) -> Self {
+                self + (-rhs)
+            }
+        }
+
+        impl<$($generics)*> std::ops::Neg for $t {
+            type Output = Self;
+            fn neg(self) -> Self {
+                let m = self.modulus();
+                self.with((m - self.value()) % m)
+            }
+        }
+
+        impl<$($generics)*> std::ops::Mul for $t {
+            type Output = Self;
+            fn mul(self, rhs: Self) -> Self {
+                debug_assert_eq!(self.modulus(), rhs.modulus());
+                self.with(mul_mod(self.value(), rhs.value(), self.modulus()))
+            }
+        }
+
+        /// Panics if `rhs` has no inverse
+        impl<$($generics)*> std::ops::Div for $t {
+            type Output = Self;
+            #[allow(clippy::suspicious_arithmetic_impl)]
+            fn div(self, rhs: Self) -> Self {
+                self * rhs.inverse().expect("Divisor is not invertible")
+            }
+        }
+
+        /// Remainder of `Div`, so that `a == (a / b) * b + a % b`: zero for any
+        /// invertible divisor, and a panic like `Div` for any other
+        impl<$($generics)*> std::ops::Rem for $t {
+            type Output = Self;
+            fn rem(self, rhs: Self) -> Self {
+                assert!(rhs.inverse().is_some(), "Divisor is not invertible");
+                self.with(0)
+            }
+        }
+
+        impl<$($generics)*> std::ops::AddAssign for $t {
+            fn add_assign(&mut self, rhs: Self) {
+                *self = *self + rhs;
+            }
+        }
+
+        impl<$($generics)*> std::ops::SubAssign for $t {
+            fn sub_assign(&mut self, rhs: Self) {
+                *self = *self - rhs;
+            }
+        }
+
+        impl<$($generics)*> std::ops::MulAssign for $t {
+            fn mul_assign(&mut self, rhs: Self) {
+                *self = *self * rhs;
+            }
+        }
+
+        impl<$($generics)*> std::fmt::Display for $t {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "{}", self.value())
+            }
+        }
+    };
+}
+
+impl_mod_ops!([const M: u64] ModInt<M>);
+impl_mod_ops!([] Mod);
+
+impl<const M: u64> num::Zero for ModInt<M> {
+    fn zero() -> Self {
+        ModInt::new(0)
+    }
+
+    fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl<const M: u64> num::One for ModInt<M> {
+    fn one() -> Self {
+        ModInt::new(1)
+    }
+}
+
+impl<const M: u64> Num for ModInt<M> {
+    type FromStrRadixErr = std::num::ParseIntError;
+
+    fn from_str_radix(str: &str, radix: u32) -> Result<Self, Self::FromStrRadixErr> {
+        i128::from_str_radix(str, radix).map(ModInt::reduced)
+    }
+}
+
+impl<const M: u64> num::traits::Pow<u64> for ModInt<M> {
+    type Output = Self;
+    fn pow(self, exp: u64) -> Self {
+        ModInt::pow(&self, exp)
+    }
+}
+
+impl<const M: u64> num::traits::Inv for ModInt<M> {
+    type Output = Self;
+    fn inv(self) -> Self {
+        self.inverse().expect("Value is not invertible")
+    }
+}
+
+/// The map x -> a*x + b (mod m)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AffineMap {
+    a: Mod,
+    b: Mod,
+}
+
+impl AffineMap {
+    pub fn new(a: i64, b: i64, modulus: u64) -> Self {
+        AffineMap {
+            a: Mod::new(a, modulus),
+            b: Mod::new(b, modulus),
+        }
+    }
+
+    pub fn identity(modulus: u64) -> Self {
+        Self::new(1, 0, modulus)
+    }
+
+    /// Returns (a, b)
+    pub fn coefficients(&self) -> (u64, u64) {
+        (self.a.value(), self.b.value())
+    }
+
+    pub fn apply(&self, x: i64) -> u64 {
+        (self.a * Mod::new(x, self.a.modulus()) + self.b).value()
+    }
+
+    /// The map that applies `self` first and `next` afterwards
+    pub fn then(&self, next: &AffineMap) -> AffineMap {
+        AffineMap {
+            a: next.a * self.a,
+            b: next.a * self.b + next.b,
+        }
+    }
+
+    /// The map undoing this one, if a is invertible
+    pub fn inverse(&self) -> Option<AffineMap> {
+        let a_inv = self.a.inverse()?;
+        Some(AffineMap {
+            a: a_inv,
+            b: -(a_inv * self.b),
+        })
+    }
+
+    /// The map applied `n` times in a row
+    pub fn pow(&self, mut n: u64) -> AffineMap {
+        let mut result = AffineMap::identity(self.a.modulus());
+        let mut square = *self;
+        while n > 0 {
+            if n & 1 == 1 {
+                result = result.then(&square);
+            }
+            square = square.then(&square);
+            n >>= 1;
+        }
+        result
+    }
+}
+
+#[test]
+fn mod_int_arithmetic() {
+    type F = ModInt<1_000_000_007>;
+
+    let a = F::new(-1);
+    assert_eq!(a.value(), 1_000_000_006);
+    assert_eq!((a * a).value(), 1);
+    assert_eq!(F::new(2).pow(1_000_000_006), F::new(1));
+    assert_eq!(F::new(3) / F::new(3), F::new(1));
+    assert_eq!(F::new(10).inverse().unwrap() * F::new(10), F::new(1));
+    assert_eq!(F::new(7) % F::new(3), F::new(0));
+
+    // Z/6 is only a ring, 2 has no inverse to divide by
+    type Z6 = ModInt<6>;
+    assert_eq!(Z6::new(2).inverse(), None);
+    assert!(std::panic::catch_unwind(|| Z6::new(4) % Z6::new(2)).is_err());
+
+    // Overflow safe close to u64::MAX
+    let big = Mod::new(-1, u64::MAX - 58);
+    assert_eq!((big * big).value(), 1);
+    assert_eq!(Mod::new(6, 9).inverse(), None);
+}
+
+#[test]
+fn affine_shuffle() {
+    // Deal into new stack, cut 3, deal with increment 7 on a 10 card deck
+    let deck = 10;
+    let shuffle = AffineMap::new(-1, -1, deck)
+        .then(&AffineMap::new(1, -3, deck))
+        .then(&AffineMap::new(7, 0, deck));
+
+    let shuffle_once = |cards: &Vec<u64>| {
+        let mut next = vec![0; cards.len()];
+        for (position, card) in cards.iter().enumerate() {
+            next[shuffle.apply(position as i64) as usize] = *card;
+        }
+        next
+    };
+
+    let mut cards: Vec<u64> = (0..deck).collect();
+    for _ in 0..37 {
+        cards = shuffle_once(&cards);
+    }
+
+    let repeated = shuffle.pow(37);
+    for (card, position) in (0..deck).map(|c| (c, repeated.apply(c as i64))) {
+        assert_eq!(cards[position as usize], card);
+    }
+
+    let inverse = shuffle.pow(1_000_000_000_000_000).inverse().unwrap();
+    let forward = shuffle.pow(1_000_000_000_000_000);
+    assert_eq!(forward.then(&inverse), AffineMap::identity(deck));
+}
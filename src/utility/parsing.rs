@@ -9,3 +9,291 @@ pub fn parse_all_numbers<T: FromStr>(line: &str) -> Vec<T> {
         .filter_map(|m| m.as_str().parse::<T>().ok())
         .collect()
 }
+
+/// Why a line does not fit a scan pattern, `column` is the byte offset where it went wrong
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScanError {
+    pub line: String,
+    pub column: usize,
+    pub message: String,
+}
+
+impl ScanError {
+    pub fn new(line: &str, column: usize, message: impl Into<String>) -> Self {
+        ScanError {
+            line: line.to_string(),
+            column,
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for ScanError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} at column {} of '{}'",
+            self.message, self.column, self.line
+        )
+    }
+}
+
+impl std::error::Error for ScanError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Piece<'a> {
+    Text(&'a str),
+    Space,
+    Field,
+}
+
+fn pieces(pattern: &str) -> Vec<Piece<'_>> {
+    let mut out = Vec::new();
+    let mut rest = pattern;
+
+    while let Some(c) = rest.chars().next() {
+        if c.is_whitespace() {
+            out.push(Piece::Space);
+            rest = rest.trim_start();
+        } else if let Some(after) = rest.strip_prefix("{}") {
+            out.push(Piece::Field);
+            rest = after;
+        } else {
+            let end = rest
+                .find(|c: char| c.is_whitespace())
+                .into_iter()
+                .chain(rest.find("{}"))
+                .min()
+                .unwrap_or(rest.len());
+            out.push(Piece::Text(&rest[..end]));
+            rest = &rest[end..];
+        }
+    }
+
+    out
+}
+
+/// Matches a line against a pattern where every `{}` is a field and any run of whitespace
+/// matches one or more whitespace characters. Returns the text of every field with its column.
+pub fn scan_fields<'a>(pattern: &str, line: &'a str) -> Result<Vec<(usize, &'a str)>, ScanError> {
+    let pieces = pieces(pattern);
+    let mut fields = Vec::new();
+    let mut pos = 0;
+
+    for (i, piece) in pieces.iter().enumerate() {
+        let rest = &line[pos..];
+        match piece {
+            Piece::Text(text) => {
+                if !rest.starts_with(text) {
+                    return Err(ScanError::new(line, pos, format!("expected '{}'", text)));
+                }
+                pos += text.len();
+            }
+            Piece::Space => {
+                let trimmed = rest.trim_start();
+                if trimmed.len() == rest.len() {
+                    return Err(ScanError::new(line, pos, "expected whitespace"));
+                }
+                pos += rest.len() - trimmed.len();
+            }
+            Piece::Field => {
+                let end = match pieces.get(i + 1) {
+                    None => Some(rest.len()),
+                    Some(Piece::Space) => {
+                        Some(rest.find(char::is_whitespace).unwrap_or(rest.len()))
+                    }
+                    Some(Piece::Text(text)) => rest.find(text),
+                    Some(Piece::Field) => {
+                        return Err(ScanError::new(
+                            line,
+                            pos,
+                            format!("pattern '{}' has two fields in a row", pattern),
+                        ));
+                    }
+                };
+                let Some(end) = end.filter(|end| *end > 0) else {
+                    return Err(ScanError::new(
+                        line,
+                        pos,
+                        format!("missing field {}", fields.len() + 1),
+                    ));
+                };
+                fields.push((pos, &rest[..end]));
+                pos += end;
+            }
+        }
+    }
+
+    if pos < line.len() {
+        return Err(ScanError::new(
+            line,
+            pos,
+            format!("unexpected trailing '{}'", &line[pos..]),
+        ));
+    }
+    Ok(fields)
+}
+
+/// Parses the next field scanned by `scan!`, naming the field and type on failure
+pub fn next_field<'a, T: FromStr>(
+    line: &str,
+    fields: &mut impl Iterator<Item = (usize, (usize, &'a str))>,
+) -> Result<T, ScanError> {
+    let (index, (column, text)) = fields
+        .next()
+        .ok_or_else(|| ScanError::new(line, line.len(), "pattern has fewer fields than types"))?;
+    text.parse().map_err(|_| {
+        ScanError::new(
+            line,
+            column,
+            format!(
+                "field {} '{}' is not a valid {}",
+                index + 1,
+                text,
+                std::any::type_name::<T>()
+            ),
+        )
+    })
+}
+
+/// Checks that `scan!` used every field
+pub fn finish_fields<'a>(
+    line: &str,
+    fields: &mut impl Iterator<Item = (usize, (usize, &'a str))>,
+) -> Result<(), ScanError> {
+    match fields.next() {
+        Some((_, (column, _))) => Err(ScanError::new(
+            line,
+            column,
+            "pattern has more fields than types",
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Scans a line into a tuple with one type per `{}` in the pattern, e.g.
+/// `scan!(line, "move {} from {} to {}", usize, usize, usize)`
+#[macro_export]
+macro_rules! scan {
+    ($line:expr, $pattern:expr, $($t:ty),+ $(,)?) => {{
+        let line: &str = $line;
+        (|| -> Result<_, $crate::utility::parsing::ScanError> {
+            let fields = $crate::utility::parsing::scan_fields($pattern, line)?;
+            let mut fields = fields.into_iter().enumerate();
+            let result = ($($crate::utility::parsing::next_field::<$t>(line, &mut fields)?,)+);
+            $crate::utility::parsing::finish_fields(line, &mut fields)?;
+            Ok(result)
+        })()
+    }};
+}
+
+/// The word in front of every occurrence of `word`, ignoring punctuation around words.
+/// For "a lithium generator, a hydrogen generator" and "generator" gives lithium, hydrogen.
+pub fn words_before<'a>(line: &'a str, word: &str) -> impl Iterator<Item = &'a str> {
+    let words = line
+        .split_whitespace()
+        .map(|w| w.trim_matches(|c: char| c.is_ascii_punctuation()))
+        .collect::<Vec<_>>();
+    let found = words
+        .windows(2)
+        .filter(|pair| pair[1] == word)
+        .map(|pair| pair[0])
+        .collect::<Vec<_>>();
+    found.into_iter()
+}
+
+#[test]
+fn scanning() {
+    let disc = "Disc #1 has 13 positions; at time=0, it is at position 11.";
+    assert_eq!(
+        scan!(
+            disc,
+            "Disc #{} has {} positions; at time={}, it is at position {}.",
+            u32,
+            u32,
+            u32,
+            u32
+        ),
+        Ok((1, 13, 0, 11))
+    );
+
+    let node = "/dev/grid/node-x0-y12     92T   72T    20T   78%";
+    assert_eq!(
+        scan!(
+            node,
+            "/dev/grid/node-x{}-y{} {}T {}T {}T {}%",
+            u8,
+            u8,
+            u8,
+            u8,
+            u8,
+            u8
+        ),
+        Ok((0, 12, 92, 72, 20, 78))
+    );
+
+    assert_eq!(
+        scan!(
+            "swap letter d with letter b",
+            "swap letter {} with letter {}",
+            char,
+            char
+        ),
+        Ok(('d', 'b'))
+    );
+    assert_eq!(
+        scan!(
+            "bot 7 gives low to output 2",
+            "bot {} gives low to {} {}",
+            u8,
+            String,
+            u8
+        ),
+        Ok((7, "output".to_string(), 2))
+    );
+}
+
+#[test]
+fn scan_errors() {
+    let message = |result: Result<(i32, i32), ScanError>| result.unwrap_err().to_string();
+
+    assert_eq!(
+        message(scan!("move 3 to 4", "move {} from {}", i32, i32)),
+        "expected 'from' at column 7 of 'move 3 to 4'"
+    );
+    assert_eq!(
+        message(scan!("move x from 2", "move {} from {}", i32, i32)),
+        "field 1 'x' is not a valid i32 at column 5 of 'move x from 2'"
+    );
+    assert_eq!(
+        message(scan!("move 1 from 2 now", "move {} from {}", i32, i32)),
+        "field 2 '2 now' is not a valid i32 at column 12 of 'move 1 from 2 now'"
+    );
+    assert_eq!(
+        message(scan!("move 1 from 2.", "move {} from {}.", i32, i32, i32).map(|t| (t.0, t.1))),
+        "pattern has fewer fields than types at column 14 of 'move 1 from 2.'"
+    );
+    assert_eq!(
+        scan!("rotate left 2 steps", "rotate {} {} step", String, usize)
+            .unwrap_err()
+            .message,
+        "unexpected trailing 's'"
+    );
+    assert_eq!(
+        message(scan!("12", "{}{}", i32, i32)),
+        "pattern '{}{}' has two fields in a row at column 0 of '12'"
+    );
+}
+
+#[test]
+fn words() {
+    let line = "The first floor contains a hydrogen-compatible microchip, a lithium generator.";
+    assert_eq!(
+        words_before(line, "microchip").collect::<Vec<_>>(),
+        vec!["hydrogen-compatible"]
+    );
+    assert_eq!(
+        words_before(line, "generator").collect::<Vec<_>>(),
+        vec!["lithium"]
+    );
+}
@@ -0,0 +1,283 @@
+use num::integer::gcd;
+
+use crate::utility::directions::IVec2;
+
+/// Where a point lies relative to a polygon
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Location {
+    Inside,
+    Boundary,
+    Outside,
+}
+
+fn edges(vertices: &[IVec2]) -> impl Iterator<Item = (IVec2, IVec2)> + '_ {
+    vertices
+        .iter()
+        .zip(vertices.iter().cycle().skip(1))
+        .map(|(a, b)| (*a, *b))
+}
+
+/// Positive when p is left of the line from a to b
+fn cross(a: IVec2, b: IVec2, p: IVec2) -> i64 {
+    (b.x - a.x) * (p.y - a.y) - (p.x - a.x) * (b.y - a.y)
+}
+
+/// Twice the area of the polygon with the shoelace formula, so it stays an integer
+pub fn double_area(vertices: &[IVec2]) -> i64 {
+    edges(vertices)
+        .map(|(a, b)| a.x * b.y - b.x * a.y)
+        .sum::<i64>()
+        .abs()
+}
+
+/// Lattice points on the edges of the polygon
+pub fn boundary_points(vertices: &[IVec2]) -> i64 {
+    edges(vertices)
+        .map(|(a, b)| gcd((b.x - a.x).abs(), (b.y - a.y).abs()))
+        .sum()
+}
+
+/// Lattice points strictly inside the polygon, from Pick's theorem A = I + B / 2 - 1
+pub fn interior_points(vertices: &[IVec2]) -> i64 {
+    (double_area(vertices) - boundary_points(vertices) + 2) / 2
+}
+
+/// Point in polygon test with the winding number, exact on integer coordinates
+pub fn locate(vertices: &[IVec2], p: IVec2) -> Location {
+    let mut winding = 0;
+
+    for (a, b) in edges(vertices) {
+        let side = cross(a, b, p);
+        let within = p.x >= a.x.min(b.x)
+            && p.x <= a.x.max(b.x)
+            && p.y >= a.y.min(b.y)
+            && p.y <= a.y.max(b.y);
+        if side == 0 && within {
+            return Location::Boundary;
+        }
+
+        if a.y <= p.y {
+            if b.y > p.y && side > 0 {
+                winding += 1;
+            }
+        } else if b.y <= p.y && side < 0 {
+            winding -= 1;
+        }
+    }
+
+    if winding != 0 {
+        Location::Inside
+    } else {
+        Location::Outside
+    }
+}
+
+/// Polygon with only horizontal and vertical edges, compressed to the grid of its
+/// vertex coordinates so rectangle queries are O(log n) with 2D prefix sums
+#[derive(Debug, Clone)]
+pub struct RectilinearPolygon {
+    vertices: Vec<IVec2>,
+    /// Vertices with every coordinate doubled, so midpoints between grid lines are integers
+    doubled: Vec<IVec2>,
+    xs: Vec<i64>,
+    ys: Vec<i64>,
+    /// Inside cells of the compressed grid in the rectangle [0, i) x [0, j)
+    prefix: Vec<Vec<i64>>,
+}
+
+impl RectilinearPolygon {
+    pub fn new(vertices: Vec<IVec2>) -> Self {
+        assert!(
+            edges(&vertices).all(|(a, b)| a.x == b.x || a.y == b.y),
+            "Polygon has a diagonal edge"
+        );
+
+        let compress = |values: Vec<i64>| {
+            let mut values = values;
+            values.sort();
+            values.dedup();
+            values
+        };
+        let xs = compress(vertices.iter().map(|v| v.x).collect());
+        let ys = compress(vertices.iter().map(|v| v.y).collect());
+
+        // Sweep every column of cells upwards, each horizontal edge spanning the column
+        // flips between outside and inside
+        let mut crossings = vec![vec![false; ys.len()]; xs.len()];
+        for (a, b) in edges(&vertices).filter(|(a, b)| a.y == b.y) {
+            let row = ys.binary_search(&a.y).unwrap();
+            let first = xs.partition_point(|x| *x < a.x.min(b.x)) + 1;
+            let last = xs.partition_point(|x| *x <= a.x.max(b.x));
+            for column in crossings.iter_mut().take(last).skip(first) {
+                column[row] ^= true;
+            }
+        }
+
+        let mut prefix = vec![vec![0; ys.len()]; xs.len()];
+        for i in 1..xs.len() {
+            let mut inside = false;
+            for j in 1..ys.len() {
+                inside ^= crossings[i][j - 1];
+                prefix[i][j] =
+                    inside as i64 + prefix[i - 1][j] + prefix[i][j - 1] - prefix[i - 1][j - 1];
+            }
+        }
+
+        RectilinearPolygon {
+            doubled: vertices.iter().map(|v| v * 2).collect(),
+            vertices,
+            xs,
+            ys,
+            prefix,
+        }
+    }
+
+    pub fn vertices(&self) -> &[IVec2] {
+        &self.vertices
+    }
+
+    pub fn locate(&self, p: IVec2) -> Location {
+        locate(&self.vertices, p)
+    }
+
+    /// Whether the rectangle with corners a and b lies in the polygon, edges included
+    pub fn contains_rect(&self, a: IVec2, b: IVec2) -> bool {
+        let (x1, x2) = (a.x.min(b.x), a.x.max(b.x));
+        let (y1, y2) = (a.y.min(b.y), a.y.max(b.y));
+
+        if x1 == x2 || y1 == y2 {
+            return self.contains_segment(IVec2::new(x1, y1), IVec2::new(x2, y2));
+        }
+        if x1 < self.xs[0] || y1 < self.ys[0] {
+            return false;
+        }
+        if x2 > *self.xs.last().unwrap() || y2 > *self.ys.last().unwrap() {
+            return false;
+        }
+
+        // Compressed cells overlapping the rectangle
+        let i1 = self.xs.partition_point(|x| *x <= x1) - 1;
+        let i2 = self.xs.partition_point(|x| *x < x2);
+        let j1 = self.ys.partition_point(|y| *y <= y1) - 1;
+        let j2 = self.ys.partition_point(|y| *y < y2);
+
+        let p = &self.prefix;
+        let inside = p[i2][j2] - p[i1][j2] - p[i2][j1] + p[i1][j1];
+        inside == ((i2 - i1) * (j2 - j1)) as i64
+    }
+
+    /// Whether an axis aligned segment stays inside or on the edge of the polygon
+    fn contains_segment(&self, a: IVec2, b: IVec2) -> bool {
+        let outside = |p: IVec2| locate(&self.doubled, p) == Location::Outside;
+
+        // Check the ends and the midpoint of every piece between grid lines
+        let mut stops = if a.x == b.x {
+            self.ys
+                .iter()
+                .filter(|y| **y > a.y && **y < b.y)
+                .map(|y| IVec2::new(a.x, *y))
+                .collect::<Vec<_>>()
+        } else {
+            self.xs
+                .iter()
+                .filter(|x| **x > a.x && **x < b.x)
+                .map(|x| IVec2::new(*x, a.y))
+                .collect::<Vec<_>>()
+        };
+        stops.insert(0, a);
+        stops.push(b);
+
+        !outside(a * 2)
+            && stops
+                .windows(2)
+                .all(|w| !outside(w[0] + w[1]) && !outside(w[1] * 2))
+    }
+}
+
+#[test]
+fn lattice_counts() {
+    let square = [(0, 0), (4, 0), (4, 4), (0, 4)].map(|(x, y)| IVec2::new(x, y));
+    assert_eq!(double_area(&square), 32);
+    assert_eq!(boundary_points(&square), 16);
+    assert_eq!(interior_points(&square), 9);
+
+    let triangle = [(0, 0), (4, 0), (0, 3)].map(|(x, y)| IVec2::new(x, y));
+    assert_eq!(double_area(&triangle), 12);
+    assert_eq!(boundary_points(&triangle), 8);
+    assert_eq!(interior_points(&triangle), 3);
+
+    assert_eq!(locate(&triangle, IVec2::new(1, 1)), Location::Inside);
+    assert_eq!(locate(&triangle, IVec2::new(2, 0)), Location::Boundary);
+    assert_eq!(locate(&triangle, IVec2::new(4, 0)), Location::Boundary);
+    assert_eq!(locate(&triangle, IVec2::new(3, 2)), Location::Outside);
+}
+
+#[test]
+fn rectangles_in_polygon() {
+    // U shape opening upwards
+    let u = [
+        (0, 0),
+        (5, 0),
+        (5, 4),
+        (3, 4),
+        (3, 2),
+        (2, 2),
+        (2, 4),
+        (0, 4),
+    ];
+    let polygon = RectilinearPolygon::new(u.map(|(x, y)| IVec2::new(x, y)).to_vec());
+
+    assert!(polygon.contains_rect(IVec2::new(0, 0), IVec2::new(5, 2)));
+    assert!(polygon.contains_rect(IVec2::new(3, 4), IVec2::new(5, 0)));
+    assert!(!polygon.contains_rect(IVec2::new(0, 0), IVec2::new(5, 3)));
+    assert!(!polygon.contains_rect(IVec2::new(1, 1), IVec2::new(4, 3)));
+    assert!(!polygon.contains_rect(IVec2::new(-1, 0), IVec2::new(1, 1)));
+
+    // Segments along the edge and across the gap
+    assert!(polygon.contains_rect(IVec2::new(0, 4), IVec2::new(2, 4)));
+    assert!(!polygon.contains_rect(IVec2::new(0, 4), IVec2::new(5, 4)));
+    assert!(polygon.contains_rect(IVec2::new(0, 2), IVec2::new(5, 2)));
+}
+
+#[test]
+fn swept_cells() {
+    // Staircase with a slot whose edges overlap the top edge
+    let shapes = [
+        vec![
+            (0, 0),
+            (6, 0),
+            (6, 2),
+            (4, 2),
+            (4, 5),
+            (1, 5),
+            (1, 3),
+            (0, 3),
+        ],
+        vec![
+            (0, 0),
+            (2, 0),
+            (2, 2),
+            (3, 2),
+            (3, 0),
+            (5, 0),
+            (5, 2),
+            (0, 2),
+        ],
+    ];
+    for shape in shapes {
+        let polygon =
+            RectilinearPolygon::new(shape.iter().map(|(x, y)| IVec2::new(*x, *y)).collect());
+        let p = &polygon.prefix;
+        for i in 1..polygon.xs.len() {
+            for j in 1..polygon.ys.len() {
+                let cell = p[i][j] - p[i - 1][j] - p[i][j - 1] + p[i - 1][j - 1];
+                let centre = IVec2::new(
+                    polygon.xs[i - 1] + polygon.xs[i],
+                    polygon.ys[j - 1] + polygon.ys[j],
+                );
+                let inside = locate(&polygon.doubled, centre) == Location::Inside;
+                assert_eq!(cell, inside as i64, "{shape:?} at cell {i}, {j}");
+            }
+        }
+    }
+}
@@ -0,0 +1,270 @@
+use std::sync::LazyLock;
+
+use crate::utility::modular::{mul_mod, pow_mod};
+
+/// Numbers below this are factorised with the shared smallest prime factor table
+const TABLE_LIMIT: usize = 1 << 20;
+
+static SMALLEST_FACTORS: LazyLock<SmallestFactors> =
+    LazyLock::new(|| SmallestFactors::new(TABLE_LIMIT));
+
+/// Smallest prime factor of every number below a limit, for O(log n) factorisation
+pub struct SmallestFactors {
+    spf: Vec<u32>,
+}
+
+impl SmallestFactors {
+    pub fn new(limit: usize) -> Self {
+        let mut spf = vec![0u32; limit.max(2)];
+        for i in 2..spf.len() {
+            if spf[i] == 0 {
+                for j in (i..spf.len()).step_by(i) {
+                    if spf[j] == 0 {
+                        spf[j] = i as u32;
+                    }
+                }
+            }
+        }
+        SmallestFactors { spf }
+    }
+
+    pub fn limit(&self) -> usize {
+        self.spf.len()
+    }
+
+    /// Prime factorisation of n as (prime, exponent) pairs, n must be below the limit
+    pub fn factorize(&self, mut n: usize) -> Vec<(u64, u32)> {
+        assert!(n < self.limit(), "{} is outside of the table", n);
+        let mut factors: Vec<(u64, u32)> = Vec::new();
+        while n > 1 {
+            let p = self.spf[n] as usize;
+            match factors.last_mut() {
+                Some((q, e)) if *q == p as u64 => *e += 1,
+                _ => factors.push((p as u64, 1)),
+            }
+            n /= p;
+        }
+        factors
+    }
+}
+
+/// All primes up to and including `limit`
+pub fn sieve(limit: usize) -> Vec<u64> {
+    let mut composite = vec![false; limit + 1];
+    let mut primes = Vec::new();
+    for i in 2..=limit {
+        if !composite[i] {
+            primes.push(i as u64);
+            for j in (i * i..=limit).step_by(i) {
+                composite[j] = true;
+            }
+        }
+    }
+    primes
+}
+
+/// All primes in `lo..hi`, sieving only that window with the primes up to sqrt(hi)
+pub fn segmented_sieve(lo: u64, hi: u64) -> Vec<u64> {
+    if hi <= lo {
+        return Vec::new();
+    }
+
+    let mut composite = vec![false; (hi - lo) as usize];
+    for p in sieve(hi.isqrt() as usize) {
+        let start = (p * p).max(lo.div_ceil(p) * p);
+        for multiple in (start..hi).step_by(p as usize) {
+            composite[(multiple - lo) as usize] = true;
+        }
+    }
+
+    (lo.max(2)..hi)
+        .filter(|n| !composite[(n - lo) as usize])
+        .collect()
+}
+
+/// Deterministic Miller-Rabin for every 64 bit value
+pub fn is_prime(n: u64) -> bool {
+    const BASES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+    if n < 2 {
+        return false;
+    }
+    for p in BASES {
+        if n.is_multiple_of(p) {
+            return n == p;
+        }
+    }
+
+    let s = (n - 1).trailing_zeros();
+    let d = (n - 1) >> s;
+
+    BASES.iter().all(|&a| {
+        let mut x = pow_mod(a, d, n);
+        if x == 1 || x == n - 1 {
+            return true;
+        }
+        for _ in 1..s {
+            x = mul_mod(x, x, n);
+            if x == n - 1 {
+                return true;
+            }
+        }
+        false
+    })
+}
+
+/// Finds a non-trivial factor of a composite n with Pollard's rho
+fn pollard_rho(n: u64) -> u64 {
+    if n.is_multiple_of(2) {
+        return 2;
+    }
+
+    for c in 1.. {
+        let f = |x: u64| ((x as u128 * x as u128 + c as u128) % n as u128) as u64;
+        let (mut x, mut y, mut d) = (2, 2, 1);
+        while d == 1 {
+            x = f(x);
+            y = f(f(y));
+            d = num::integer::gcd(x.abs_diff(y), n);
+        }
+        if d != n {
+            return d;
+        }
+    }
+    unreachable!()
+}
+
+fn collect_prime_factors(n: u64, primes: &mut Vec<u64>) {
+    if n == 1 {
+        return;
+    }
+    if (n as usize) < TABLE_LIMIT {
+        for (p, e) in SMALLEST_FACTORS.factorize(n as usize) {
+            primes.extend(std::iter::repeat_n(p, e as usize));
+        }
+    } else if is_prime(n) {
+        primes.push(n);
+    } else {
+        let d = pollard_rho(n);
+        collect_prime_factors(d, primes);
+        collect_prime_factors(n / d, primes);
+    }
+}
+
+/// Prime factorisation of n as (prime, exponent) pairs in increasing order
+pub fn factorize(n: u64) -> Vec<(u64, u32)> {
+    let mut primes = Vec::new();
+    collect_prime_factors(n, &mut primes);
+    primes.sort();
+
+    primes.into_iter().fold(Vec::new(), |mut acc, p| {
+        match acc.last_mut() {
+            Some((q, e)) if *q == p => *e += 1,
+            _ => acc.push((p, 1)),
+        }
+        acc
+    })
+}
+
+/// All divisors of n in increasing order, built from its factorisation.
+/// Empty for 0, which every number divides.
+pub fn divisors(n: u64) -> Vec<u64> {
+    if n == 0 {
+        return Vec::new();
+    }
+
+    let mut divisors = vec![1];
+    for (p, e) in factorize(n) {
+        let current = divisors.len();
+        let mut power = 1;
+        for _ in 0..e {
+            power *= p;
+            for i in 0..current {
+                divisors.push(divisors[i] * power);
+            }
+        }
+    }
+    divisors.sort();
+    divisors
+}
+
+/// Euler's totient, the count of numbers in 1..=n coprime with n
+pub fn totient(n: u64) -> u64 {
+    factorize(n)
+        .into_iter()
+        .fold(n, |acc, (p, _)| acc / p * (p - 1))
+}
+
+/// Möbius function: 0 if n has a squared factor, otherwise -1 to the number of primes
+pub fn mobius(n: u64) -> i8 {
+    let factors = factorize(n);
+    if factors.iter().any(|(_, e)| *e > 1) {
+        0
+    } else if factors.len().is_multiple_of(2) {
+        1
+    } else {
+        -1
+    }
+}
+
+/// Sum of the k-th powers of all divisors of n (sigma_k), k = 0 counts the divisors
+pub fn divisor_sum(n: u64, k: u32) -> u64 {
+    factorize(n).into_iter().fold(1, |acc, (p, e)| {
+        let pk = p.pow(k);
+        acc * (0..=e).map(|i| pk.pow(i)).sum::<u64>()
+    })
+}
+
+#[test]
+fn sieves() {
+    assert_eq!(sieve(30), vec![2, 3, 5, 7, 11, 13, 17, 19, 23, 29]);
+    assert_eq!(segmented_sieve(0, 30), sieve(29));
+    assert_eq!(
+        segmented_sieve(1_000_000_000, 1_000_000_100),
+        vec![
+            1_000_000_007,
+            1_000_000_009,
+            1_000_000_021,
+            1_000_000_033,
+            1_000_000_087,
+            1_000_000_093,
+            1_000_000_097
+        ]
+    );
+}
+
+#[test]
+fn primality() {
+    assert!(!is_prime(1));
+    assert!(is_prime(2));
+    assert!(is_prime(18_446_744_073_709_551_557));
+    // Strong pseudoprime to the first few bases
+    assert!(!is_prime(3_215_031_751));
+    assert!(!is_prime(1_000_000_007 * 998_244_353));
+}
+
+#[test]
+fn factorisation() {
+    assert_eq!(factorize(1), vec![]);
+    assert_eq!(factorize(360), vec![(2, 3), (3, 2), (5, 1)]);
+    assert_eq!(
+        factorize(1_000_000_007 * 998_244_353 * 4),
+        vec![(2, 2), (998_244_353, 1), (1_000_000_007, 1)]
+    );
+    assert_eq!(divisors(12), vec![1, 2, 3, 4, 6, 12]);
+    assert_eq!(divisors(1), vec![1]);
+    assert_eq!(divisors(0), vec![]);
+    assert_eq!(divisors(12), crate::utility::modular::get_divisors(12));
+}
+
+#[test]
+fn arithmetic_functions() {
+    assert_eq!(totient(36), 12);
+    assert_eq!(totient(1_000_000_007), 1_000_000_006);
+    assert_eq!(mobius(30), -1);
+    assert_eq!(mobius(12), 0);
+    assert_eq!(mobius(1), 1);
+    assert_eq!(divisor_sum(12, 0), 6);
+    assert_eq!(divisor_sum(12, 1), 28);
+    assert_eq!(divisor_sum(12, 2), 210);
+}
@@ -1,8 +1,251 @@
-fn merge_range(a: &(i64, i64), b: &(i64, i64)) -> Option<(i64, i64)> {
-    let (a_start, a_end) = *a;
-    let (b_start, b_end) = *b;
-    if a_end < b_start || b_end < a_start {
-        return None;
+use std::{
+    collections::BTreeMap,
+    ops::{Bound, RangeBounds},
+};
+
+use num::PrimInt;
+
+/// Set of integers stored as disjoint inclusive intervals, touching intervals are merged.
+/// Inserting or removing a range costs O(log n) plus the intervals it swallows.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct IntervalSet<T: PrimInt> {
+    /// Start to end of every interval, both inclusive
+    intervals: BTreeMap<T, T>,
+}
+
+/// Converts any range into inclusive bounds, None when it is empty
+fn inclusive_bounds<T: PrimInt, R: RangeBounds<T>>(range: &R) -> Option<(T, T)> {
+    let start = match range.start_bound() {
+        Bound::Included(s) => *s,
+        Bound::Excluded(s) => s.checked_add(&T::one())?,
+        Bound::Unbounded => T::min_value(),
+    };
+    let end = match range.end_bound() {
+        Bound::Included(e) => *e,
+        Bound::Excluded(e) => e.checked_sub(&T::one())?,
+        Bound::Unbounded => T::max_value(),
+    };
+    (start <= end).then_some((start, end))
+}
+
+/// e - s for s <= e, subtracting in T where that cannot overflow
+fn width<T: PrimInt>(s: T, e: T) -> u128 {
+    if s >= T::zero() || e < T::zero() {
+        (e - s).to_u128().unwrap()
+    } else {
+        // s < 0 <= e, so -(s + 1) fits in T
+        e.to_u128().unwrap() + (T::zero() - (s + T::one())).to_u128().unwrap() + 1
+    }
+}
+
+impl<T: PrimInt> IntervalSet<T> {
+    pub fn new() -> Self {
+        IntervalSet {
+            intervals: BTreeMap::new(),
+        }
+    }
+
+    /// Set holding a single range, e.g. `2..=5` or the half open `2..6`
+    pub fn from_range<R: RangeBounds<T>>(range: R) -> Self {
+        let mut set = Self::new();
+        set.insert(range);
+        set
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// Number of disjoint intervals
+    pub fn interval_count(&self) -> usize {
+        self.intervals.len()
+    }
+
+    /// Disjoint inclusive intervals in increasing order
+    pub fn iter(&self) -> impl Iterator<Item = (T, T)> + '_ {
+        self.intervals.iter().map(|(s, e)| (*s, *e))
+    }
+
+    /// Interval containing x
+    pub fn interval_of(&self, x: T) -> Option<(T, T)> {
+        let (s, e) = self.intervals.range(..=x).next_back()?;
+        (*e >= x).then_some((*s, *e))
+    }
+
+    pub fn contains(&self, x: T) -> bool {
+        self.interval_of(x).is_some()
+    }
+
+    /// Number of integers in the set, panics when it covers every value of a 128 bit type
+    pub fn len(&self) -> u128 {
+        self.iter()
+            .map(|(s, e)| width(s, e).checked_add(1))
+            .try_fold(0u128, |acc, n| acc.checked_add(n?))
+            .expect("Interval set has more than u128::MAX integers")
+    }
+
+    pub fn insert<R: RangeBounds<T>>(&mut self, range: R) {
+        let Some((mut start, mut end)) = inclusive_bounds(&range) else {
+            return;
+        };
+
+        // An interval before start that reaches it or touches it
+        if let Some((&s, &e)) = self.intervals.range(..=start).next_back()
+            && e.saturating_add(T::one()) >= start
+        {
+            self.intervals.remove(&s);
+            start = s;
+            end = end.max(e);
+        }
+
+        // Intervals starting inside the new one or right after it
+        let reach = end.saturating_add(T::one());
+        while let Some((&s, &e)) = self.intervals.range(start..).next()
+            && s <= reach
+        {
+            self.intervals.remove(&s);
+            end = end.max(e);
+        }
+
+        self.intervals.insert(start, end);
+    }
+
+    pub fn remove<R: RangeBounds<T>>(&mut self, range: R) {
+        let Some((start, end)) = inclusive_bounds(&range) else {
+            return;
+        };
+
+        // Split an interval that starts before the removed range
+        if let Some((&s, &e)) = self.intervals.range(..start).next_back()
+            && e >= start
+        {
+            self.intervals.insert(s, start - T::one());
+            if e > end {
+                self.intervals.insert(end + T::one(), e);
+            }
+        }
+
+        while let Some((&s, &e)) = self.intervals.range(start..).next()
+            && s <= end
+        {
+            self.intervals.remove(&s);
+            if e > end {
+                self.intervals.insert(end + T::one(), e);
+            }
+        }
     }
-    Some((a_start.min(b_start), a_end.max(b_end)))
+
+    /// Adds every interval of other to this set
+    pub fn merge(&mut self, other: &IntervalSet<T>) {
+        for (s, e) in other.iter() {
+            self.insert(s..=e);
+        }
+    }
+
+    pub fn union(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut out = self.clone();
+        out.merge(other);
+        out
+    }
+
+    pub fn difference(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut out = self.clone();
+        for (s, e) in other.iter() {
+            out.remove(s..=e);
+        }
+        out
+    }
+
+    pub fn intersection(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        self.difference(&self.difference(other))
+    }
+
+    /// Everything in `universe` that is not in the set
+    pub fn complement<R: RangeBounds<T>>(&self, universe: R) -> IntervalSet<T> {
+        IntervalSet::from_range(universe).difference(self)
+    }
+
+    pub fn is_subset(&self, other: &IntervalSet<T>) -> bool {
+        self.difference(other).is_empty()
+    }
+
+    pub fn intersects(&self, other: &IntervalSet<T>) -> bool {
+        !self.intersection(other).is_empty()
+    }
+
+    /// Inclusive ranges between consecutive intervals
+    pub fn gaps(&self) -> impl Iterator<Item = (T, T)> + '_ {
+        self.intervals
+            .iter()
+            .zip(self.intervals.iter().skip(1))
+            .map(|((_, e), (s, _))| (*e + T::one(), *s - T::one()))
+    }
+}
+
+impl<T: PrimInt, R: RangeBounds<T>> FromIterator<R> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = R>>(iter: I) -> Self {
+        let mut set = IntervalSet::new();
+        for range in iter {
+            set.insert(range);
+        }
+        set
+    }
+}
+
+#[test]
+fn insert_and_remove() {
+    let mut set: IntervalSet<i64> = [1..=3, 10..=12, 5..=6].into_iter().collect();
+    assert_eq!(set.interval_count(), 3);
+
+    set.insert(4..5);
+    assert_eq!(set.iter().collect::<Vec<_>>(), vec![(1, 6), (10, 12)]);
+    assert_eq!(set.gaps().collect::<Vec<_>>(), vec![(7, 9)]);
+
+    set.remove(3..=10);
+    assert_eq!(set.iter().collect::<Vec<_>>(), vec![(1, 2), (11, 12)]);
+    assert_eq!(set.len(), 4);
+    assert!(set.contains(11));
+    assert!(!set.contains(5));
+
+    set.insert(-5..=20);
+    set.remove(0..1);
+    assert_eq!(set.iter().collect::<Vec<_>>(), vec![(-5, -1), (1, 20)]);
+}
+
+#[test]
+fn set_operations() {
+    let a = IntervalSet::from_range(0u8..=10);
+    let b: IntervalSet<u8> = [5..=7, 9..=20].into_iter().collect();
+
+    assert_eq!(a.union(&b).iter().collect::<Vec<_>>(), vec![(0, 20)]);
+    assert_eq!(
+        a.intersection(&b).iter().collect::<Vec<_>>(),
+        vec![(5, 7), (9, 10)]
+    );
+    assert_eq!(
+        a.difference(&b).iter().collect::<Vec<_>>(),
+        vec![(0, 4), (8, 8)]
+    );
+    assert!(IntervalSet::from_range(6..=7).is_subset(&b));
+    assert!(!a.is_subset(&b));
+
+    // Ranges reaching the ends of the type
+    let full = b.complement(..);
+    assert_eq!(
+        full.iter().collect::<Vec<_>>(),
+        vec![(0, 4), (8, 8), (21, 255)]
+    );
+    assert_eq!(full.union(&b).len(), 256);
+
+    assert_eq!(IntervalSet::<i64>::from_range(..).len(), 1 << 64);
+    assert_eq!(IntervalSet::<u128>::from_range(1..).len(), u128::MAX);
+    assert_eq!(
+        IntervalSet::<i128>::from_range(i128::MIN + 1..).len(),
+        u128::MAX
+    );
+    assert_eq!(
+        IntervalSet::<i128>::from_range(-3..=i128::MAX).len(),
+        (1 << 127) + 3
+    );
+    assert!(std::panic::catch_unwind(|| IntervalSet::<i128>::from_range(..).len()).is_err());
 }
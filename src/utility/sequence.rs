@@ -0,0 +1,327 @@
+use crate::utility::modular::ModInt;
+
+/// Sequence that repeats with `period` from index `start` onwards
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    pub start: usize,
+    pub period: usize,
+}
+
+impl Cycle {
+    /// Index below `start + period` that holds the same value as index n
+    pub fn reduce(&self, n: u64) -> usize {
+        let start = self.start as u64;
+        if n < start {
+            n as usize
+        } else {
+            (start + (n - start) % self.period as u64) as usize
+        }
+    }
+}
+
+/// Floyd's tortoise and hare on the sequence x0, f(x0), f(f(x0)), ...
+pub fn floyd<T: PartialEq, F: Fn(&T) -> T>(x0: T, f: F) -> Cycle {
+    let mut tortoise = f(&x0);
+    let mut hare = f(&tortoise);
+    while tortoise != hare {
+        tortoise = f(&tortoise);
+        hare = f(&f(&hare));
+    }
+
+    let mut start = 0;
+    let mut tortoise = x0;
+    while tortoise != hare {
+        tortoise = f(&tortoise);
+        hare = f(&hare);
+        start += 1;
+    }
+
+    let mut period = 1;
+    let mut hare = f(&tortoise);
+    while tortoise != hare {
+        hare = f(&hare);
+        period += 1;
+    }
+
+    Cycle { start, period }
+}
+
+/// Brent's algorithm, fewer evaluations of f than Floyd's
+pub fn brent<T: PartialEq + Clone, F: Fn(&T) -> T>(x0: T, f: F) -> Cycle {
+    let mut power = 1;
+    let mut period = 1;
+    let mut tortoise = x0.clone();
+    let mut hare = f(&x0);
+    while tortoise != hare {
+        if power == period {
+            tortoise = hare.clone();
+            power *= 2;
+            period = 0;
+        }
+        hare = f(&hare);
+        period += 1;
+    }
+
+    let mut tortoise = x0.clone();
+    let mut hare = x0;
+    for _ in 0..period {
+        hare = f(&hare);
+    }
+
+    let mut start = 0;
+    while tortoise != hare {
+        tortoise = f(&tortoise);
+        hare = f(&hare);
+        start += 1;
+    }
+
+    Cycle { start, period }
+}
+
+/// Shortest period that a generated prefix settles into, seen at least `min_repeats` times
+pub fn find_period<T: PartialEq>(values: &[T], min_repeats: usize) -> Option<Cycle> {
+    (1..=values.len() / min_repeats.max(1)).find_map(|period| {
+        // First index from which every value matches the one a period later
+        let start = (0..values.len() - period)
+            .rev()
+            .find(|i| values[*i] != values[i + period])
+            .map_or(0, |i| i + 1);
+
+        (values.len() - start >= min_repeats * period).then_some(Cycle { start, period })
+    })
+}
+
+/// Polynomial stored in Newton form, f(n) = sum of C(n, j) * differences[j]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Polynomial {
+    differences: Vec<i64>,
+}
+
+impl Polynomial {
+    pub fn degree(&self) -> usize {
+        self.differences.len().saturating_sub(1)
+    }
+
+    /// Value at index n, panics if it does not fit in an i128
+    pub fn eval(&self, n: u64) -> i128 {
+        let n = n as i128;
+        let mut binomial = 1i128;
+        let mut sum = 0i128;
+
+        for (j, d) in self.differences.iter().enumerate() {
+            let term = binomial
+                .checked_mul(*d as i128)
+                .expect("Polynomial overflowed");
+            sum = sum.checked_add(term).expect("Polynomial overflowed");
+            binomial = binomial
+                .checked_mul(n - j as i128)
+                .expect("Polynomial overflowed")
+                / (j as i128 + 1);
+        }
+
+        sum
+    }
+}
+
+/// Lowest degree polynomial through `values`, found with finite differences.
+/// Needs one more value than the degree needs so the fit is confirmed.
+pub fn fit_polynomial(values: &[i64], max_degree: usize) -> Option<Polynomial> {
+    let mut differences = Vec::new();
+    let mut row = values.to_vec();
+
+    while differences.len() <= max_degree && row.len() > 1 {
+        differences.push(row[0]);
+        row = row.windows(2).map(|w| w[1] - w[0]).collect();
+        if row.iter().all(|v| *v == 0) {
+            return Some(Polynomial { differences });
+        }
+    }
+
+    None
+}
+
+/// a(n) = sum of coefficients[i] * a(n - 1 - i) over GF(P)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinearRecurrence<const P: u64> {
+    coefficients: Vec<ModInt<P>>,
+    initial: Vec<ModInt<P>>,
+}
+
+impl<const P: u64> LinearRecurrence<P> {
+    pub fn new(coefficients: Vec<ModInt<P>>, initial: Vec<ModInt<P>>) -> Self {
+        assert!(initial.len() >= coefficients.len());
+        LinearRecurrence {
+            coefficients,
+            initial,
+        }
+    }
+
+    pub fn order(&self) -> usize {
+        self.coefficients.len()
+    }
+
+    pub fn coefficients(&self) -> &[ModInt<P>] {
+        &self.coefficients
+    }
+
+    /// Product of two polynomials reduced by the characteristic polynomial
+    fn mul_reduce(&self, a: &[ModInt<P>], b: &[ModInt<P>]) -> Vec<ModInt<P>> {
+        let order = self.order();
+        let mut product = vec![ModInt::new(0); 2 * order - 1];
+        for (i, x) in a.iter().enumerate() {
+            for (j, y) in b.iter().enumerate() {
+                product[i + j] += *x * *y;
+            }
+        }
+
+        // x^order = sum of coefficients[i] * x^(order - 1 - i)
+        for k in (order..product.len()).rev() {
+            let lead = product[k];
+            for (i, c) in self.coefficients.iter().enumerate() {
+                product[k - 1 - i] += lead * *c;
+            }
+        }
+
+        product.truncate(order);
+        product
+    }
+
+    /// Term at index n in O(order^2 log n) by computing x^n modulo the characteristic polynomial
+    pub fn nth(&self, n: u64) -> ModInt<P> {
+        if n < self.initial.len() as u64 {
+            return self.initial[n as usize];
+        }
+
+        let order = self.order();
+        if order == 0 {
+            return ModInt::new(0);
+        }
+
+        let mut result = vec![ModInt::new(0); order];
+        result[0] = ModInt::new(1);
+        let mut base = vec![ModInt::new(0); order];
+        if order == 1 {
+            base[0] = self.coefficients[0];
+        } else {
+            base[1] = ModInt::new(1);
+        }
+
+        let mut exp = n;
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = self.mul_reduce(&result, &base);
+            }
+            base = self.mul_reduce(&base, &base);
+            exp >>= 1;
+        }
+
+        result
+            .iter()
+            .zip(&self.initial)
+            .fold(ModInt::new(0), |acc, (r, a)| acc + *r * *a)
+    }
+}
+
+/// Shortest linear recurrence generating `values` over GF(P), P must be prime
+pub fn berlekamp_massey<const P: u64>(values: &[ModInt<P>]) -> LinearRecurrence<P> {
+    let zero = ModInt::<P>::new(0);
+    let mut current = vec![ModInt::<P>::new(1)];
+    let mut previous = current.clone();
+    let mut length = 0;
+    let mut shift = 1;
+    let mut previous_discrepancy = ModInt::<P>::new(1);
+
+    for n in 0..values.len() {
+        let discrepancy = (1..=length).fold(values[n], |acc, i| acc + current[i] * values[n - i]);
+        if discrepancy == zero {
+            shift += 1;
+            continue;
+        }
+
+        let factor = discrepancy / previous_discrepancy;
+        let before = current.clone();
+        if current.len() < previous.len() + shift {
+            current.resize(previous.len() + shift, zero);
+        }
+        for (i, b) in previous.iter().enumerate() {
+            current[i + shift] -= factor * *b;
+        }
+
+        if 2 * length <= n {
+            length = n + 1 - length;
+            previous = before;
+            previous_discrepancy = discrepancy;
+            shift = 1;
+        } else {
+            shift += 1;
+        }
+    }
+
+    current.resize(length + 1, zero);
+    let coefficients = current[1..].iter().map(|c| -*c).collect();
+    LinearRecurrence::new(coefficients, values[..length].to_vec())
+}
+
+#[test]
+fn cycles() {
+    let f = |x: &u64| (x * x + 1) % 255;
+
+    let mut seen = std::collections::HashMap::new();
+    let mut x = 3;
+    for i in 0.. {
+        if let Some(start) = seen.insert(x, i) {
+            let expected = Cycle {
+                start,
+                period: i - start,
+            };
+            assert_eq!(floyd(3, f), expected);
+            assert_eq!(brent(3, f), expected);
+            break;
+        }
+        x = f(&x);
+    }
+
+    let values = [5, 1, 2, 3, 4, 2, 3, 4, 2, 3, 4];
+    let cycle = find_period(&values, 2).unwrap();
+    assert_eq!(
+        cycle,
+        Cycle {
+            start: 2,
+            period: 3
+        }
+    );
+    assert_eq!(values[cycle.reduce(1_000_000_000_000)], 4);
+    assert_eq!(find_period(&[1, 2, 3, 1, 2], 2), None);
+}
+
+#[test]
+fn polynomials() {
+    // Bottom right corners of the square spiral, (2n + 1)^2
+    let corners = [1, 9, 25, 49, 81];
+    let p = fit_polynomial(&corners, 3).unwrap();
+    assert_eq!(p.degree(), 2);
+    assert_eq!(p.eval(3), 49);
+    assert_eq!(p.eval(1_000_000_000_000), (2_000_000_000_001i128).pow(2));
+
+    assert_eq!(fit_polynomial(&[1, 9, 25], 3), None);
+    assert_eq!(fit_polynomial(&[1, 2, 4, 8, 16, 32], 3), None);
+}
+
+#[test]
+fn recurrences() {
+    type F = ModInt<1_000_000_007>;
+
+    // 3 * 2^n + 5^n satisfies a(n) = 7 a(n - 1) - 10 a(n - 2)
+    let term = |n: u64| F::new(3) * F::new(2).pow(n) + F::new(5).pow(n);
+    let values = (0..10).map(term).collect::<Vec<_>>();
+
+    let recurrence = berlekamp_massey(&values);
+    assert_eq!(recurrence.coefficients(), &[F::new(7), F::new(-10)]);
+    assert_eq!(recurrence.nth(9), values[9]);
+    assert_eq!(recurrence.nth(1_000_000_000_000), term(1_000_000_000_000));
+
+    let fibonacci = [1, 1, 2, 3, 5, 8, 13, 21].map(F::new);
+    let recurrence = berlekamp_massey(&fibonacci);
+    assert_eq!(recurrence.order(), 2);
+    assert_eq!(recurrence.nth(89), F::new(2_880_067_194_370_816_120i64));
+}
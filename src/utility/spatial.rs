@@ -0,0 +1,254 @@
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use nalgebra::Vector3;
+
+pub type IVec3 = Vector3<i64>;
+
+/// Exact squared Euclidean distance
+pub fn squared_distance(a: &IVec3, b: &IVec3) -> i64 {
+    (a - b).map(|v| v * v).sum()
+}
+
+/// k-d tree over 3D integer points, stored implicitly as a median ordered array.
+/// Results are (squared distance, index of the point in the input) and ties are
+/// broken by index, so every query has a single well defined answer.
+#[derive(Debug, Clone)]
+pub struct KdTree {
+    points: Vec<(IVec3, usize)>,
+}
+
+impl KdTree {
+    pub fn new(points: &[IVec3]) -> Self {
+        fn build(items: &mut [(IVec3, usize)], depth: usize) {
+            if items.len() <= 1 {
+                return;
+            }
+            let axis = depth % 3;
+            let mid = items.len() / 2;
+            items.select_nth_unstable_by_key(mid, |(p, _)| p[axis]);
+            let (left, right) = items.split_at_mut(mid);
+            build(left, depth + 1);
+            build(&mut right[1..], depth + 1);
+        }
+
+        let mut points = points
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (*p, i))
+            .collect::<Vec<_>>();
+        build(&mut points, 0);
+        KdTree { points }
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    fn nearest_in(
+        &self,
+        (lo, hi): (usize, usize),
+        depth: usize,
+        query: &IVec3,
+        k: usize,
+        skip: Option<usize>,
+        best: &mut BinaryHeap<(i64, usize)>,
+    ) {
+        if lo >= hi {
+            return;
+        }
+        let mid = (lo + hi) / 2;
+        let (p, index) = &self.points[mid];
+
+        if skip != Some(*index) {
+            best.push((squared_distance(p, query), *index));
+            if best.len() > k {
+                best.pop();
+            }
+        }
+
+        let axis = depth % 3;
+        let diff = query[axis] - p[axis];
+        let (near, far) = if diff < 0 {
+            ((lo, mid), (mid + 1, hi))
+        } else {
+            ((mid + 1, hi), (lo, mid))
+        };
+
+        self.nearest_in(near, depth + 1, query, k, skip, best);
+        if best.len() < k || diff * diff <= best.peek().unwrap().0 {
+            self.nearest_in(far, depth + 1, query, k, skip, best);
+        }
+    }
+
+    /// The k points closest to the query, nearest first
+    pub fn nearest(&self, query: &IVec3, k: usize) -> Vec<(i64, usize)> {
+        self.nearest_except(query, k, None)
+    }
+
+    /// Like `nearest` but leaving out the point with index `skip`, e.g. the query itself
+    pub fn nearest_except(
+        &self,
+        query: &IVec3,
+        k: usize,
+        skip: Option<usize>,
+    ) -> Vec<(i64, usize)> {
+        if k == 0 {
+            return Vec::new();
+        }
+        let mut best = BinaryHeap::new();
+        self.nearest_in((0, self.len()), 0, query, k, skip, &mut best);
+        best.into_sorted_vec()
+    }
+
+    /// Every point within `radius` of the query, nearest first
+    pub fn within(&self, query: &IVec3, radius: i64) -> Vec<(i64, usize)> {
+        let limit = radius * radius;
+        let mut found = Vec::new();
+        let mut stack = vec![(0, self.len(), 0)];
+
+        while let Some((lo, hi, depth)) = stack.pop() {
+            if lo >= hi {
+                continue;
+            }
+            let mid = (lo + hi) / 2;
+            let (p, index) = &self.points[mid];
+
+            let d = squared_distance(p, query);
+            if d <= limit {
+                found.push((d, *index));
+            }
+
+            let axis = depth % 3;
+            let diff = query[axis] - p[axis];
+            if diff >= -radius {
+                stack.push((mid + 1, hi, depth + 1));
+            }
+            if diff <= radius {
+                stack.push((lo, mid, depth + 1));
+            }
+        }
+
+        found.sort();
+        found
+    }
+}
+
+/// Pairs of points in increasing distance order, ties broken by index, each pair once
+/// with the smaller index first. Neighbour lists are grown on demand per point, so
+/// taking the first k pairs never builds all n^2 of them.
+pub struct ClosestPairs<'a> {
+    tree: KdTree,
+    points: &'a [IVec3],
+    neighbours: Vec<Vec<(i64, usize)>>,
+    /// Next unused neighbour of every point, ordered by (distance, point, neighbour)
+    queue: BinaryHeap<Reverse<(i64, usize, usize, usize)>>,
+}
+
+impl ClosestPairs<'_> {
+    /// Neighbour `rank` of point i, fetching twice as many neighbours when it runs out
+    fn neighbour(&mut self, i: usize, rank: usize) -> Option<(i64, usize)> {
+        if rank >= self.neighbours[i].len() && self.neighbours[i].len() < self.points.len() - 1 {
+            let k = (2 * rank).max(4);
+            self.neighbours[i] = self.tree.nearest_except(&self.points[i], k, Some(i));
+        }
+        self.neighbours[i].get(rank).copied()
+    }
+
+    fn push(&mut self, i: usize, rank: usize) {
+        if let Some((d, j)) = self.neighbour(i, rank) {
+            self.queue.push(Reverse((d, i, j, rank)));
+        }
+    }
+}
+
+impl Iterator for ClosestPairs<'_> {
+    type Item = (i64, usize, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let Reverse((d, i, j, rank)) = self.queue.pop()?;
+            self.push(i, rank + 1);
+
+            // Every pair shows up from both ends, keep the one from the smaller index
+            if i < j {
+                return Some((d, i, j));
+            }
+        }
+    }
+}
+
+pub fn closest_pairs(points: &[IVec3]) -> ClosestPairs<'_> {
+    let mut pairs = ClosestPairs {
+        tree: KdTree::new(points),
+        points,
+        neighbours: vec![Vec::new(); points.len()],
+        queue: BinaryHeap::new(),
+    };
+    for i in 0..points.len() {
+        pairs.push(i, 0);
+    }
+    pairs
+}
+
+#[test]
+fn nearest_neighbours() {
+    let mut seed = 7u64;
+    let mut next = || {
+        seed = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        ((seed >> 33) % 2000) as i64 - 1000
+    };
+    let points = (0..300)
+        .map(|_| IVec3::new(next(), next(), next()))
+        .collect::<Vec<_>>();
+    let tree = KdTree::new(&points);
+
+    let query = IVec3::new(5, -30, 12);
+    let mut brute = points
+        .iter()
+        .enumerate()
+        .map(|(i, p)| (squared_distance(p, &query), i))
+        .collect::<Vec<_>>();
+    brute.sort();
+
+    assert_eq!(tree.nearest(&query, 10), brute[..10].to_vec());
+    let radius = 400;
+    let inside = brute
+        .iter()
+        .copied()
+        .filter(|(d, _)| *d <= radius * radius)
+        .collect::<Vec<_>>();
+    assert_eq!(tree.within(&query, radius), inside);
+
+    let mut pairs = Vec::new();
+    for i in 0..points.len() {
+        for j in i + 1..points.len() {
+            pairs.push((squared_distance(&points[i], &points[j]), i, j));
+        }
+    }
+    pairs.sort();
+    assert_eq!(
+        closest_pairs(&points).take(2000).collect::<Vec<_>>(),
+        pairs[..2000]
+    );
+}
+
+#[test]
+fn exact_distances() {
+    // Too close together for f32 to tell apart
+    let points = [
+        IVec3::new(100_000_000, 0, 0),
+        IVec3::new(0, 0, 0),
+        IVec3::new(0, 100_000_001, 0),
+    ];
+    let pairs = closest_pairs(&points).collect::<Vec<_>>();
+    assert_eq!(
+        pairs.iter().map(|p| (p.1, p.2)).collect::<Vec<_>>(),
+        vec![(0, 1), (1, 2), (0, 2)]
+    );
+}
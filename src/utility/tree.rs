@@ -1,48 +1,314 @@
-use std::cell::{Ref, RefCell};
-use std::rc::{Rc, Weak};
+use std::collections::VecDeque;
 
-type RcRefCell<T> = Rc<RefCell<T>>;
-type WeakRefCell<T> = Weak<RefCell<T>>;
+pub type NodeId = usize;
 
-#[derive(Debug)]
-pub struct Node<T> {
+#[derive(Debug, Clone)]
+struct TreeNode<T> {
     value: T,
-    parent: RefCell<Weak<Node<T>>>,
-    children: RefCell<Vec<Rc<Node<T>>>>,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+    depth: usize,
 }
 
-impl<T> Node<T> {
-    pub fn new(value: T) -> Rc<Node<T>> {
-        Rc::new(Node {
+/// Rooted tree stored as an arena, nodes are addressed by the id returned when adding them
+#[derive(Debug, Clone)]
+pub struct Tree<T> {
+    nodes: Vec<TreeNode<T>>,
+}
+
+impl<T> Tree<T> {
+    pub fn new(root: T) -> Self {
+        Tree {
+            nodes: vec![TreeNode {
+                value: root,
+                parent: None,
+                children: Vec::new(),
+                depth: 0,
+            }],
+        }
+    }
+
+    pub fn root(&self) -> NodeId {
+        0
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// A tree always holds its root
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    pub fn add_child(&mut self, parent: NodeId, value: T) -> NodeId {
+        let id = self.nodes.len();
+        let depth = self.nodes[parent].depth + 1;
+        self.nodes.push(TreeNode {
             value,
-            parent: RefCell::new(Weak::new()),
-            children: RefCell::new(vec![]),
+            parent: Some(parent),
+            children: Vec::new(),
+            depth,
+        });
+        self.nodes[parent].children.push(id);
+        id
+    }
+
+    pub fn get(&self, id: NodeId) -> &T {
+        &self.nodes[id].value
+    }
+
+    pub fn get_mut(&mut self, id: NodeId) -> &mut T {
+        &mut self.nodes[id].value
+    }
+
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self.nodes[id].parent
+    }
+
+    pub fn children(&self, id: NodeId) -> &[NodeId] {
+        &self.nodes[id].children
+    }
+
+    /// Number of edges between the node and the root
+    pub fn depth(&self, id: NodeId) -> usize {
+        self.nodes[id].depth
+    }
+
+    /// The node and its descendants, parents before children
+    pub fn pre_order(&self, from: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        let mut stack = vec![from];
+        std::iter::from_fn(move || {
+            let id = stack.pop()?;
+            stack.extend(self.children(id).iter().rev());
+            Some(id)
+        })
+    }
+
+    /// The node and its descendants, children before parents
+    pub fn post_order(&self, from: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        // Each entry is a node and whether its children are already on the stack
+        let mut stack = vec![(from, false)];
+        std::iter::from_fn(move || {
+            loop {
+                let (id, expanded) = stack.pop()?;
+                if expanded {
+                    return Some(id);
+                }
+                stack.push((id, true));
+                stack.extend(self.children(id).iter().rev().map(|c| (*c, false)));
+            }
         })
     }
 
-    pub fn add_child(parent: &Rc<Node<T>>, child: Rc<Node<T>>) {
-        *child.parent.borrow_mut() = Rc::downgrade(parent);
-        parent.children.borrow_mut().push(child);
+    /// The node and its descendants, level by level
+    pub fn bfs(&self, from: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        let mut queue = VecDeque::from([from]);
+        std::iter::from_fn(move || {
+            let id = queue.pop_front()?;
+            queue.extend(self.children(id));
+            Some(id)
+        })
+    }
+
+    /// Combines every subtree bottom up, `f` gets a node and the results of its children.
+    /// Returns the result for every node, indexed by id.
+    pub fn fold<U, F>(&self, mut f: F) -> Vec<U>
+    where
+        F: FnMut(&T, &[&U]) -> U,
+    {
+        let mut results: Vec<Option<U>> = (0..self.len()).map(|_| None).collect();
+        for id in self.post_order(self.root()) {
+            let children = self
+                .children(id)
+                .iter()
+                .map(|c| results[*c].as_ref().unwrap())
+                .collect::<Vec<_>>();
+            let result = f(self.get(id), &children);
+            results[id] = Some(result);
+        }
+        results.into_iter().map(Option::unwrap).collect()
+    }
+
+    pub fn lowest_common_ancestor(&self, a: NodeId, b: NodeId) -> NodeId {
+        let (mut a, mut b) = (a, b);
+        while self.depth(a) > self.depth(b) {
+            a = self.parent(a).unwrap();
+        }
+        while self.depth(b) > self.depth(a) {
+            b = self.parent(b).unwrap();
+        }
+        while a != b {
+            a = self.parent(a).unwrap();
+            b = self.parent(b).unwrap();
+        }
+        a
     }
 
-    pub fn children(&'_ self) -> Ref<'_, Vec<Rc<Node<T>>>> {
-        self.children.borrow()
+    /// Ids from the root down to the node, both included
+    pub fn path_from_root(&self, id: NodeId) -> Vec<NodeId> {
+        let mut path = std::iter::successors(Some(id), |n| self.parent(*n)).collect::<Vec<_>>();
+        path.reverse();
+        path
     }
+}
 
-    pub fn parent(&self) -> Option<Rc<Node<T>>> {
-        self.parent.borrow().upgrade()
+impl<T> std::ops::Index<NodeId> for Tree<T> {
+    type Output = T;
+    fn index(&self, id: NodeId) -> &Self::Output {
+        self.get(id)
     }
 }
 
-impl<T> std::ops::Deref for Node<T> {
-    type Target = T;
-    fn deref(&self) -> &Self::Target {
-        &self.value
+impl<T> std::ops::IndexMut<NodeId> for Tree<T> {
+    fn index_mut(&mut self, id: NodeId) -> &mut Self::Output {
+        self.get_mut(id)
     }
 }
 
-impl<T> std::ops::DerefMut for Node<T> {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.value
+/// Values that are found by name among their siblings, like files in a directory
+pub trait Named {
+    fn name(&self) -> &str;
+}
+
+impl Named for String {
+    fn name(&self) -> &str {
+        self
     }
 }
+
+impl<T: Named> Tree<T> {
+    pub fn child_named(&self, id: NodeId, name: &str) -> Option<NodeId> {
+        self.children(id)
+            .iter()
+            .copied()
+            .find(|c| self.get(*c).name() == name)
+    }
+}
+
+/// Builds a tree by walking it like a shell, with `cd` moves and `ls` style listings
+#[derive(Debug, Clone)]
+pub struct PathBuilder<T> {
+    tree: Tree<T>,
+    current: NodeId,
+}
+
+impl<T: Named> PathBuilder<T> {
+    pub fn new(root: T) -> Self {
+        PathBuilder {
+            tree: Tree::new(root),
+            current: 0,
+        }
+    }
+
+    pub fn current(&self) -> NodeId {
+        self.current
+    }
+
+    pub fn tree(&self) -> &Tree<T> {
+        &self.tree
+    }
+
+    /// Moves to the root for `/`, the parent for `..` and otherwise to the named child,
+    /// creating it with `make` if it has not been seen yet
+    pub fn cd(&mut self, target: &str, make: impl FnOnce(&str) -> T) {
+        self.current = match target {
+            "/" => self.tree.root(),
+            ".." => self.tree.parent(self.current).unwrap_or(self.current),
+            _ => match self.tree.child_named(self.current, target) {
+                Some(child) => child,
+                None => self.tree.add_child(self.current, make(target)),
+            },
+        };
+    }
+
+    /// Adds a listed entry to the current node, keeping the existing one on a repeated listing
+    pub fn add(&mut self, value: T) -> NodeId {
+        match self.tree.child_named(self.current, value.name()) {
+            Some(child) => child,
+            None => self.tree.add_child(self.current, value),
+        }
+    }
+
+    /// Replays `$ cd <name>` and `$ ls` lines, every other line is an entry parsed by `entry`
+    pub fn replay<E, D>(&mut self, transcript: &str, mut entry: E, mut directory: D)
+    where
+        E: FnMut(&str) -> T,
+        D: FnMut(&str) -> T,
+    {
+        for line in transcript.lines() {
+            if let Some(target) = line.strip_prefix("$ cd ") {
+                self.cd(target.trim(), &mut directory);
+            } else if !line.starts_with('$') && !line.trim().is_empty() {
+                self.add(entry(line));
+            }
+        }
+    }
+
+    pub fn build(self) -> Tree<T> {
+        self.tree
+    }
+}
+
+#[cfg(test)]
+fn sample() -> Tree<&'static str> {
+    //       a
+    //     / | \
+    //    b  c  d
+    //   / \     \
+    //  e   f     g
+    let mut tree = Tree::new("a");
+    let b = tree.add_child(0, "b");
+    tree.add_child(0, "c");
+    let d = tree.add_child(0, "d");
+    tree.add_child(b, "e");
+    tree.add_child(b, "f");
+    tree.add_child(d, "g");
+    tree
+}
+
+#[test]
+fn traversals() {
+    let tree = sample();
+    let names = |ids: Vec<NodeId>| ids.into_iter().map(|i| tree[i]).collect::<String>();
+
+    assert_eq!(names(tree.pre_order(0).collect()), "abefcdg");
+    assert_eq!(names(tree.post_order(0).collect()), "efbcgda");
+    assert_eq!(names(tree.bfs(0).collect()), "abcdefg");
+    assert_eq!(names(tree.pre_order(1).collect()), "bef");
+
+    let sizes = tree.fold(|_, children: &[&usize]| 1 + children.iter().copied().sum::<usize>());
+    assert_eq!(sizes, vec![7, 3, 1, 2, 1, 1, 1]);
+}
+
+#[test]
+fn ancestors() {
+    let tree = sample();
+    let id = |name| (0..tree.len()).find(|i| tree[*i] == name).unwrap();
+
+    assert_eq!(tree.lowest_common_ancestor(id("e"), id("f")), id("b"));
+    assert_eq!(tree.lowest_common_ancestor(id("e"), id("g")), id("a"));
+    assert_eq!(tree.lowest_common_ancestor(id("b"), id("f")), id("b"));
+    assert_eq!(
+        tree.path_from_root(id("g")),
+        vec![id("a"), id("d"), id("g")]
+    );
+    assert_eq!(tree.depth(id("f")), 2);
+}
+
+#[test]
+fn transcripts() {
+    let mut builder = PathBuilder::new("/".to_string());
+    builder.replay(
+        "$ cd /\n$ ls\ndir a\nb\n$ cd a\n$ ls\nc\n$ cd ..\n$ ls\ndir a\nb\n$ cd x",
+        |line| line.trim_start_matches("dir ").to_string(),
+        |name| name.to_string(),
+    );
+    let tree = builder.build();
+
+    let a = tree.child_named(0, "a").unwrap();
+    assert_eq!(tree.len(), 5);
+    assert_eq!(tree.children(0).len(), 3);
+    assert!(tree.child_named(a, "c").is_some());
+    assert!(tree.child_named(0, "x").is_some());
+}
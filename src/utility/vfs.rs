@@ -0,0 +1,298 @@
+use std::fmt::Write;
+
+use crate::utility::tree::{Named, NodeId, PathBuilder, Tree};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Entry {
+    Directory(String),
+    File(String, u64),
+}
+
+impl Named for Entry {
+    fn name(&self) -> &str {
+        match self {
+            Entry::Directory(name) => name,
+            Entry::File(name, _) => name,
+        }
+    }
+}
+
+/// Why a transcript does not describe a single filesystem
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VfsError {
+    /// A line that is neither a known command nor a listing entry
+    BadLine(String),
+    /// The same file listed again with another size
+    SizeChanged { path: String, was: u64, now: u64 },
+    /// A name listed once as a file and once as a directory
+    KindChanged(String),
+    /// `cd` into something that is a file
+    NotADirectory(String),
+}
+
+impl std::fmt::Display for VfsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VfsError::BadLine(line) => write!(f, "cannot parse line '{}'", line),
+            VfsError::SizeChanged { path, was, now } => {
+                write!(f, "{} listed with size {} and {}", path, was, now)
+            }
+            VfsError::KindChanged(path) => write!(f, "{} listed as a file and a directory", path),
+            VfsError::NotADirectory(path) => write!(f, "cannot cd into file {}", path),
+        }
+    }
+}
+
+impl std::error::Error for VfsError {}
+
+/// In-memory filesystem rebuilt from a `$ cd` / `$ ls` shell transcript, with the
+/// total size of every node computed once up front
+#[derive(Debug, Clone)]
+pub struct Vfs {
+    tree: Tree<Entry>,
+    sizes: Vec<u64>,
+}
+
+fn parse_entry(line: &str) -> Result<Entry, VfsError> {
+    let bad_line = || VfsError::BadLine(line.to_string());
+    let (kind, name) = line.split_once(' ').ok_or_else(bad_line)?;
+    Ok(match kind {
+        "dir" => Entry::Directory(name.to_string()),
+        _ => Entry::File(name.to_string(), kind.parse().map_err(|_| bad_line())?),
+    })
+}
+
+impl Vfs {
+    pub fn from_transcript(transcript: &str) -> Result<Self, VfsError> {
+        let mut builder = PathBuilder::new(Entry::Directory(String::new()));
+
+        for line in transcript.lines().filter(|l| !l.trim().is_empty()) {
+            if let Some(target) = line.strip_prefix("$ cd ") {
+                let tree = builder.tree();
+                if let Some(child) = tree.child_named(builder.current(), target)
+                    && matches!(tree[child], Entry::File(..))
+                {
+                    return Err(VfsError::NotADirectory(path_of(tree, child)));
+                }
+                builder.cd(target, |name| Entry::Directory(name.to_string()));
+            } else if line.trim() == "$ ls" {
+                continue;
+            } else if line.starts_with('$') {
+                return Err(VfsError::BadLine(line.to_string()));
+            } else {
+                let entry = parse_entry(line)?;
+                let tree = builder.tree();
+                if let Some(existing) = tree.child_named(builder.current(), entry.name()) {
+                    let path = path_of(tree, existing);
+                    match (&tree[existing], &entry) {
+                        (Entry::File(_, was), Entry::File(_, now)) if was != now => {
+                            return Err(VfsError::SizeChanged {
+                                path,
+                                was: *was,
+                                now: *now,
+                            });
+                        }
+                        (Entry::File(..), Entry::Directory(_))
+                        | (Entry::Directory(_), Entry::File(..)) => {
+                            return Err(VfsError::KindChanged(path));
+                        }
+                        _ => {}
+                    }
+                }
+                builder.add(entry);
+            }
+        }
+
+        let tree = builder.build();
+        let sizes = tree.fold(|entry, children: &[&u64]| match entry {
+            Entry::Directory(_) => children.iter().copied().sum(),
+            Entry::File(_, size) => *size,
+        });
+        Ok(Vfs { tree, sizes })
+    }
+
+    pub fn tree(&self) -> &Tree<Entry> {
+        &self.tree
+    }
+
+    pub fn root(&self) -> NodeId {
+        self.tree.root()
+    }
+
+    /// Size of a file, or of everything below a directory
+    pub fn size(&self, id: NodeId) -> u64 {
+        self.sizes[id]
+    }
+
+    pub fn used(&self) -> u64 {
+        self.size(self.root())
+    }
+
+    pub fn is_directory(&self, id: NodeId) -> bool {
+        matches!(self.tree[id], Entry::Directory(_))
+    }
+
+    /// Absolute path like `/a/e`, the root is `/`
+    pub fn path(&self, id: NodeId) -> String {
+        path_of(&self.tree, id)
+    }
+
+    pub fn lookup(&self, path: &str) -> Option<NodeId> {
+        path.split('/')
+            .filter(|part| !part.is_empty())
+            .try_fold(self.root(), |id, part| self.tree.child_named(id, part))
+    }
+
+    /// Every directory, the root first
+    pub fn directories(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.tree
+            .pre_order(self.root())
+            .filter(|id| self.is_directory(*id))
+    }
+
+    /// Directories with a total size of at most `limit`
+    pub fn directories_at_most(&self, limit: u64) -> Vec<NodeId> {
+        self.directories()
+            .filter(|id| self.size(*id) <= limit)
+            .collect()
+    }
+
+    /// Smallest directory whose deletion leaves at least `needed` free on a disk of `capacity`
+    pub fn smallest_to_free(&self, capacity: u64, needed: u64) -> Option<NodeId> {
+        let free = capacity.saturating_sub(self.used());
+        let missing = needed.saturating_sub(free);
+        self.directories()
+            .filter(|id| self.size(*id) >= missing)
+            .min_by_key(|id| self.size(*id))
+    }
+
+    /// Indented listing like the puzzle text, e.g. `- a (dir)` and `- b.txt (file, size=14848514)`
+    pub fn render_tree(&self) -> String {
+        let mut out = String::new();
+        for id in self.tree.pre_order(self.root()) {
+            let indent = "  ".repeat(self.tree.depth(id));
+            let name = if id == self.root() {
+                "/"
+            } else {
+                self.tree[id].name()
+            };
+            match &self.tree[id] {
+                Entry::Directory(_) => writeln!(out, "{}- {} (dir)", indent, name),
+                Entry::File(_, size) => writeln!(out, "{}- {} (file, size={})", indent, name, size),
+            }
+            .unwrap();
+        }
+        out
+    }
+
+    /// Directory totals like `du`, children before their parents
+    pub fn render_du(&self) -> String {
+        let mut out = String::new();
+        for id in self.tree.post_order(self.root()) {
+            if self.is_directory(id) {
+                writeln!(out, "{}\t{}", self.size(id), self.path(id)).unwrap();
+            }
+        }
+        out
+    }
+}
+
+fn path_of(tree: &Tree<Entry>, id: NodeId) -> String {
+    if id == tree.root() {
+        return "/".to_string();
+    }
+    tree.path_from_root(id)
+        .into_iter()
+        .skip(1)
+        .fold(String::new(), |path, n| path + "/" + tree[n].name())
+}
+
+#[cfg(test)]
+const SAMPLE: &str = "$ cd /
+$ ls
+dir a
+14848514 b.txt
+8504156 c.dat
+dir d
+$ cd a
+$ ls
+dir e
+29116 f
+2557 g
+62596 h.lst
+$ cd e
+$ ls
+584 i
+$ cd ..
+$ cd ..
+$ cd d
+$ ls
+4060174 j
+8033020 d.log
+5626152 d.ext
+7214296 k";
+
+#[test]
+fn sizes_and_queries() {
+    let vfs = Vfs::from_transcript(SAMPLE).unwrap();
+    let e = vfs.lookup("/a/e").unwrap();
+
+    assert_eq!(vfs.used(), 48381165);
+    assert_eq!(vfs.size(e), 584);
+    assert_eq!(vfs.path(e), "/a/e");
+    assert_eq!(vfs.lookup("/d/k").map(|k| vfs.size(k)), Some(7214296));
+    assert_eq!(vfs.lookup("/x"), None);
+
+    let small = vfs.directories_at_most(100000);
+    assert_eq!(small.iter().map(|d| vfs.size(*d)).sum::<u64>(), 95437);
+
+    let freed = vfs.smallest_to_free(70000000, 30000000).unwrap();
+    assert_eq!(vfs.path(freed), "/d");
+    assert_eq!(vfs.size(freed), 24933642);
+}
+
+#[test]
+fn listings() {
+    let vfs = Vfs::from_transcript(SAMPLE).unwrap();
+    let tree = vfs.render_tree();
+    assert!(
+        tree.starts_with("- / (dir)\n  - a (dir)\n    - e (dir)\n      - i (file, size=584)\n")
+    );
+    assert_eq!(
+        vfs.render_du(),
+        "584\t/a/e\n94853\t/a\n24933642\t/d\n48381165\t/\n"
+    );
+}
+
+#[test]
+fn inconsistent_transcripts() {
+    let twice = "$ cd /\n$ ls\n10 a\n$ ls\n12 a";
+    assert_eq!(
+        Vfs::from_transcript(twice).unwrap_err(),
+        VfsError::SizeChanged {
+            path: "/a".to_string(),
+            was: 10,
+            now: 12
+        }
+    );
+
+    let relisted = "$ cd /\n$ ls\n10 a\n$ ls\n10 a";
+    assert!(Vfs::from_transcript(relisted).is_ok());
+
+    let kind = "$ ls\ndir a\n$ ls\n10 a";
+    assert_eq!(
+        Vfs::from_transcript(kind).unwrap_err(),
+        VfsError::KindChanged("/a".to_string())
+    );
+
+    let into_file = "$ ls\n10 a\n$ cd a";
+    assert_eq!(
+        Vfs::from_transcript(into_file).unwrap_err(),
+        VfsError::NotADirectory("/a".to_string())
+    );
+
+    assert!(matches!(
+        Vfs::from_transcript("$ rm -rf /"),
+        Err(VfsError::BadLine(_))
+    ));
+}
@@ -0,0 +1,235 @@
+use std::ops::{Index, IndexMut};
+
+/// Fixed-size register file, addressed by index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Registers<V> {
+    values: Vec<V>,
+}
+
+impl<V: Clone + Default> Registers<V> {
+    /// Creates a register file with `count` registers, all set to the default value.
+    pub fn new(count: usize) -> Self {
+        Registers {
+            values: vec![V::default(); count],
+        }
+    }
+}
+
+impl<V> Registers<V> {
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &V> {
+        self.values.iter()
+    }
+}
+
+impl<V> Index<usize> for Registers<V> {
+    type Output = V;
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.values[index]
+    }
+}
+
+impl<V> IndexMut<usize> for Registers<V> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.values[index]
+    }
+}
+
+/// What the machine should do after an instruction has executed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step<V> {
+    Next,
+    Jump(i64),
+    Output(V),
+    Halt,
+}
+
+pub trait InstructionSet: Clone + Sized {
+    type Value: Clone + Default;
+
+    /// Number of cycles the instruction takes to complete
+    fn cycles(&self) -> usize {
+        1
+    }
+
+    /// Executes the instruction at `ip`. The whole program is passed in so that
+    /// self-modifying instructions can rewrite it.
+    fn execute(
+        &self,
+        registers: &mut Registers<Self::Value>,
+        ip: usize,
+        code: &mut [Self],
+    ) -> Step<Self::Value>;
+}
+
+/// Register machine with an instruction pointer and a cycle counter.
+#[derive(Debug, Clone)]
+pub struct Machine<V> {
+    pub registers: Registers<V>,
+    instruction_ptr: usize,
+    cycle: usize,
+    halted: bool,
+}
+
+impl<V: Clone + Default> Machine<V> {
+    pub fn new(register_count: usize) -> Self {
+        Machine {
+            registers: Registers::new(register_count),
+            instruction_ptr: 0,
+            cycle: 0,
+            halted: false,
+        }
+    }
+}
+
+impl<V: Clone> Machine<V> {
+    pub fn instruction_ptr(&self) -> usize {
+        self.instruction_ptr
+    }
+
+    /// Number of cycles completed so far
+    pub fn cycle(&self) -> usize {
+        self.cycle
+    }
+
+    pub fn halted(&self) -> bool {
+        self.halted
+    }
+
+    /// Moves the instruction pointer back to the start of the program, keeping registers.
+    pub fn reset(&mut self) {
+        self.instruction_ptr = 0;
+        self.cycle = 0;
+        self.halted = false;
+    }
+
+    /// Moves past a block of instructions whose effect has already been applied
+    /// to the registers, counting the cycles it would have taken.
+    pub fn skip(&mut self, instructions: usize, cycles: usize) {
+        self.instruction_ptr += instructions;
+        self.cycle += cycles;
+    }
+
+    /// Executes a single instruction. `observer` is called once for every cycle the
+    /// instruction takes, with the 1-based cycle number and the registers as they are
+    /// *during* that cycle (before the instruction has taken effect).
+    /// Returns None once the program has halted.
+    pub fn step<I, F>(&mut self, code: &mut [I], observer: &mut F) -> Option<Step<V>>
+    where
+        I: InstructionSet<Value = V>,
+        F: FnMut(usize, &Registers<V>),
+    {
+        if self.halted || self.instruction_ptr >= code.len() {
+            self.halted = true;
+            return None;
+        }
+
+        let instruction = code[self.instruction_ptr].clone();
+
+        for _ in 0..instruction.cycles() {
+            self.cycle += 1;
+            observer(self.cycle, &self.registers);
+        }
+
+        let step = instruction.execute(&mut self.registers, self.instruction_ptr, code);
+
+        match &step {
+            Step::Next | Step::Output(_) => self.instruction_ptr += 1,
+            Step::Jump(offset) => {
+                let target = self.instruction_ptr as i64 + offset;
+                if target < 0 {
+                    self.halted = true;
+                } else {
+                    self.instruction_ptr = target as usize;
+                }
+            }
+            Step::Halt => self.halted = true,
+        }
+
+        Some(step)
+    }
+
+    /// Runs until the program outputs a value or halts, firing `observer` every cycle.
+    pub fn run_observed<I, F>(&mut self, code: &mut [I], mut observer: F) -> Option<V>
+    where
+        I: InstructionSet<Value = V>,
+        F: FnMut(usize, &Registers<V>),
+    {
+        while let Some(step) = self.step(code, &mut observer) {
+            if let Step::Output(v) = step {
+                return Some(v);
+            }
+        }
+        None
+    }
+
+    /// Runs until the program outputs a value or halts.
+    pub fn run<I>(&mut self, code: &mut [I]) -> Option<V>
+    where
+        I: InstructionSet<Value = V>,
+    {
+        self.run_observed(code, |_, _| {})
+    }
+}
+
+#[derive(Clone)]
+enum TestOp {
+    Add(usize, i64),
+    Jnz(usize, i64),
+}
+
+impl InstructionSet for TestOp {
+    type Value = i64;
+
+    fn cycles(&self) -> usize {
+        match self {
+            TestOp::Add(..) => 2,
+            TestOp::Jnz(..) => 1,
+        }
+    }
+
+    fn execute(&self, registers: &mut Registers<i64>, _: usize, _: &mut [Self]) -> Step<i64> {
+        match *self {
+            TestOp::Add(r, v) => {
+                registers[r] += v;
+                Step::Next
+            }
+            TestOp::Jnz(r, offset) => {
+                if registers[r] != 0 {
+                    Step::Jump(offset)
+                } else {
+                    Step::Next
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn counts_cycles() {
+    // a = 3; loop { b += 10; a -= 1 } while a != 0
+    let mut code = vec![
+        TestOp::Add(0, 3),
+        TestOp::Add(1, 10),
+        TestOp::Add(0, -1),
+        TestOp::Jnz(0, -2),
+    ];
+    let mut machine = Machine::new(2);
+    let mut seen = Vec::new();
+    let out = machine.run_observed(&mut code, |cycle, r| seen.push((cycle, r[1])));
+
+    assert_eq!(out, None);
+    assert!(machine.halted());
+    assert_eq!(machine.registers[1], 30);
+    assert_eq!(machine.cycle(), 2 + 3 * 5);
+    assert_eq!(seen.len(), machine.cycle());
+    // Both cycles of the first add still see the old value
+    assert_eq!(&seen[2..5], &[(3, 0), (4, 0), (5, 10)]);
+}
@@ -1,30 +1,28 @@
 use std::collections::HashMap;
 
-use crate::check_result;
-use lazy_static::lazy_static;
-use regex::Regex;
-
-lazy_static! {
-    static ref VAL_REGEX: Regex = Regex::new(r"^value (\d+) goes to bot (\d+)$").unwrap();
-    static ref GIVE_REGEX: Regex =
-        Regex::new(r"^bot (\d+) gives low to (bot|output) (\d+) and high to (bot|output) (\d+)$")
-            .unwrap();
-}
+use crate::{check_result, scan};
 
 enum Target {
     Robot(usize),
     Output(usize),
 }
 
+impl Target {
+    fn new(kind: &str, index: usize) -> Target {
+        match kind {
+            "bot" => Target::Robot(index),
+            "output" => Target::Output(index),
+            _ => panic!("Unknown target {}", kind),
+        }
+    }
+}
+
 type Node = (Target, Target);
 
 type State = [Vec<usize>; 256];
 
 fn setup_init(mut acc: Box<State>, s: &str) -> Box<State> {
-    if let Some(captures) = VAL_REGEX.captures(s) {
-        let value: usize = captures[1].parse().unwrap();
-        let robot: usize = captures[2].parse().unwrap();
-
+    if let Ok((value, robot)) = scan!(s, "value {} goes to bot {}", usize, usize) {
         acc[robot].push(value);
         assert!(acc[robot].len() <= 2);
     }
@@ -33,22 +31,12 @@ fn setup_init(mut acc: Box<State>, s: &str) -> Box<State> {
 }
 
 fn setup_graph(mut acc: HashMap<usize, Node>, s: &str) -> HashMap<usize, Node> {
-    if let Some(captures) = GIVE_REGEX.captures(s) {
-        let from: usize = captures[1].parse().unwrap();
-        let low_v: usize = captures[3].parse().unwrap();
-        let high_v: usize = captures[5].parse().unwrap();
-
-        let low = match &captures[2] {
-            "bot" => Target::Robot(low_v),
-            "output" => Target::Output(low_v),
-            _ => panic!(),
-        };
-
-        let high = match &captures[4] {
-            "bot" => Target::Robot(high_v),
-            "output" => Target::Output(high_v),
-            _ => panic!(),
-        };
+    let pattern = "bot {} gives low to {} {} and high to {} {}";
+    if let Ok((from, low_kind, low, high_kind, high)) =
+        scan!(s, pattern, usize, String, usize, String, usize)
+    {
+        let low = Target::new(&low_kind, low);
+        let high = Target::new(&high_kind, high);
 
         assert!(acc.insert(from, (low, high)).is_none());
     }
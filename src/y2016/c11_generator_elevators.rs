@@ -1,10 +1,9 @@
-use lazy_static::lazy_static;
-use regex::Regex;
 use std::collections::{HashMap, HashSet, VecDeque};
 
-lazy_static! {
-    static ref CHIP_REGEX: Regex = Regex::new(r"(\w+)-compatible microchip").unwrap();
-    static ref GEN_REGEX: Regex = Regex::new(r"(\w+) generator").unwrap();
+use crate::utility::parsing::words_before;
+
+fn chips(line: &str) -> impl Iterator<Item = &str> {
+    words_before(line, "microchip").filter_map(|w| w.strip_suffix("-compatible"))
 }
 
 #[derive(Debug, Default, Hash, PartialEq, Eq, Clone)]
@@ -16,9 +15,8 @@ struct State {
 fn parse_initial_state(s: &str) -> State {
     let mut out = State::default();
 
-    let name_indices: HashMap<String, usize> = CHIP_REGEX
-        .captures_iter(s)
-        .map(|caps| caps[1].to_string())
+    let name_indices: HashMap<String, usize> = chips(s)
+        .map(|name| name.to_string())
         .collect::<HashSet<String>>() // remove duplicates
         .into_iter()
         .enumerate()
@@ -28,13 +26,13 @@ fn parse_initial_state(s: &str) -> State {
     out.items.resize(name_indices.len(), (42, 42));
 
     for (floor, line) in s.lines().enumerate() {
-        for m in CHIP_REGEX.captures_iter(line) {
-            let index = name_indices.get(&m[1]).copied().unwrap();
+        for name in chips(line) {
+            let index = name_indices.get(name).copied().unwrap();
             out.items[index].0 = floor;
         }
 
-        for m in GEN_REGEX.captures_iter(line) {
-            let index = name_indices.get(&m[1]).copied().unwrap();
+        for name in words_before(line, "generator") {
+            let index = name_indices.get(name).copied().unwrap();
             out.items[index].1 = floor;
         }
     }
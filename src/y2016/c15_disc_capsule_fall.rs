@@ -1,12 +1,7 @@
-use crate::{check_result, utility};
-use lazy_static::lazy_static;
-use regex::Regex;
-
-lazy_static! {
-    static ref DISC_REGEX: Regex =
-        Regex::new(r"^Disc #(\d+) has (\d+) positions; at time=(\d+), it is at position (\d+).$")
-            .unwrap();
-}
+use crate::{
+    check_result, scan,
+    utility::{self, parsing::ScanError},
+};
 
 fn into_equation(&(index, length, time, current): &(i64, i64, i64, i64)) -> (i64, i64) {
     (
@@ -16,31 +11,27 @@ fn into_equation(&(index, length, time, current): &(i64, i64, i64, i64)) -> (i64
 }
 
 // (remainder, length)
-fn parse_disc(s: &str) -> Option<(i64, i64)> {
-    if let Some(capture) = DISC_REGEX.captures(s) {
-        let index = capture[1].parse::<i64>().ok()?;
-        let length = capture[2].parse::<i64>().ok()?;
-        let time = capture[3].parse::<i64>().ok()?;
-        let current = capture[4].parse::<i64>().ok()?;
-
-        Some(into_equation(&(index, length, time, current)))
-    } else {
-        None
-    }
+fn parse_disc(s: &str) -> Result<(i64, i64), ScanError> {
+    let disc = scan!(
+        s,
+        "Disc #{} has {} positions; at time={}, it is at position {}.",
+        i64,
+        i64,
+        i64,
+        i64
+    )?;
+
+    Ok(into_equation(&disc))
 }
 
-fn constructive_crt(input: &Vec<(i64, i64)>) -> i64 {
-    let product_mod = input.iter().fold(1i64, |acc, (_, l)| acc * (*l));
-    let mut sum = 0;
-
-    for &(remainder, modulus) in input.iter() {
-        let mod_exclude = product_mod / modulus;
-        let modular_inverse = utility::modular::modular_inverse(mod_exclude, modulus).unwrap();
-
-        sum += remainder * mod_exclude * modular_inverse
-    }
+fn first_alignment(input: &[(i64, i64)]) -> i64 {
+    let congruences: Vec<(i128, i128)> = input
+        .iter()
+        .map(|&(remainder, modulus)| (remainder.into(), modulus.into()))
+        .collect();
 
-    utility::modular::positive_mod(sum, product_mod)
+    let (time, _) = utility::modular::crt(&congruences).unwrap();
+    time as i64
 }
 
 fn challenge(input: &str) -> (i64, i64) {
@@ -50,12 +41,12 @@ fn challenge(input: &str) -> (i64, i64) {
         .map(|s| parse_disc(s).unwrap())
         .collect();
 
-    let part1 = constructive_crt(&input);
+    let part1 = first_alignment(&input);
 
     let new_disc = ((input.len() + 1) as i64, 11i64, 0i64, 0i64);
     input.push(into_equation(&new_disc));
 
-    (part1, constructive_crt(&input))
+    (part1, first_alignment(&input))
 }
 
 check_result!("input/Y2016/C15.txt", 376777, 3903937);
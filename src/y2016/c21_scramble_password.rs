@@ -1,20 +1,4 @@
-use crate::check_result;
-use lazy_static::lazy_static;
-use regex::Regex;
-
-lazy_static! {
-    static ref PARSE_REGEX: Regex = Regex::new(
-        r"^(?x)
-        swap\ position\ (\d+)\ with\ position\ (\d+)
-        |swap\ letter\ ([a-z])\ with\ letter\ ([a-z])
-        |reverse\ positions\ (\d+)\ through\ (\d+)
-        |rotate\ (left|right)\ (\d+)\ step(?:s)?
-        |move\ position\ (\d+)\ to\ position\ (\d+)
-        |rotate\ based\ on\ position\ of\ letter\ ([a-z])
-        $"
-    )
-    .unwrap();
-}
+use crate::{check_result, scan, utility::parsing::ScanError};
 
 #[derive(Debug)]
 enum Instruction {
@@ -27,32 +11,28 @@ enum Instruction {
     RotateBasedOnLetter(u8),
 }
 
-fn parse_instruction(line: &str) -> Option<Instruction> {
-    let caps = PARSE_REGEX.captures(line)?;
-
-    Some(if let Some(pos1) = caps.get(1) {
-        Instruction::SwapPosition(pos1.as_str().parse().unwrap(), caps[2].parse().unwrap())
-    } else if let Some(ch1) = caps.get(3) {
-        Instruction::SwapLetter(
-            ch1.as_str().chars().next().unwrap() as u8,
-            caps[4].chars().next().unwrap() as u8,
-        )
-    } else if let Some(pos1) = caps.get(5) {
-        Instruction::ReversePositions(pos1.as_str().parse().unwrap(), caps[6].parse().unwrap())
-    } else if let Some(dir) = caps.get(7) {
-        let n = caps[8].parse().unwrap();
-        match dir.as_str() {
-            "left" => Instruction::RotateLeft(n),
-            "right" => Instruction::RotateRight(n),
-            _ => return None,
-        }
-    } else if let Some(pos1) = caps.get(9) {
-        Instruction::MovePosition(pos1.as_str().parse().unwrap(), caps[10].parse().unwrap())
-    } else if let Some(ch) = caps.get(11) {
-        Instruction::RotateBasedOnLetter(ch.as_str().chars().next().unwrap() as u8)
-    } else {
-        return None;
-    })
+fn parse_instruction(line: &str) -> Result<Instruction, ScanError> {
+    Ok(
+        if let Ok((a, b)) = scan!(line, "swap position {} with position {}", usize, usize) {
+            Instruction::SwapPosition(a, b)
+        } else if let Ok((a, b)) = scan!(line, "swap letter {} with letter {}", char, char) {
+            Instruction::SwapLetter(a as u8, b as u8)
+        } else if let Ok((a, b)) = scan!(line, "reverse positions {} through {}", usize, usize) {
+            Instruction::ReversePositions(a, b)
+        } else if let Ok((a, b)) = scan!(line, "move position {} to position {}", usize, usize) {
+            Instruction::MovePosition(a, b)
+        } else if let Ok((c,)) = scan!(line, "rotate based on position of letter {}", char) {
+            Instruction::RotateBasedOnLetter(c as u8)
+        } else {
+            let (direction, n) = scan!(line, "rotate {} {} step", String, usize)
+                .or_else(|e| scan!(line, "rotate {} {} steps", String, usize).map_err(|_| e))?;
+            match direction.as_str() {
+                "left" => Instruction::RotateLeft(n),
+                "right" => Instruction::RotateRight(n),
+                _ => return Err(ScanError::new(line, 7, "expected left or right")),
+            }
+        },
+    )
 }
 
 fn process(mut vec: Vec<u8>, instruction: &Instruction) -> Vec<u8> {
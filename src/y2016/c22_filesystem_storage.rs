@@ -1,11 +1,7 @@
-use crate::{check_result, utility::matrix::MatrixVec};
-use lazy_static::lazy_static;
-use regex::Regex;
-
-lazy_static! {
-    static ref PARSE_REGEX: Regex =
-        Regex::new(r"^/dev/grid/node-x(\d+)-y(\d+)\s+(\d+)T\s+(\d+)T\s+(\d+)T\s+(\d+)%$").unwrap();
-}
+use crate::{
+    check_result, scan,
+    utility::{matrix::MatrixVec, parsing::ScanError},
+};
 
 #[derive(Debug, Clone, Default)]
 struct DiskInfo {
@@ -15,23 +11,27 @@ struct DiskInfo {
     percent: usize,
 }
 
-fn parse_disk(line: &str) -> Option<((usize, usize), DiskInfo)> {
-    if let Some(captures) = PARSE_REGEX.captures(line) {
-        Some((
-            (
-                captures[1].parse::<usize>().ok()?,
-                captures[2].parse::<usize>().ok()?,
-            ),
-            DiskInfo {
-                size: captures[3].parse().ok()?,
-                used: captures[4].parse().ok()?,
-                avail: captures[5].parse().ok()?,
-                percent: captures[6].parse().ok()?,
-            },
-        ))
-    } else {
-        None
-    }
+fn parse_disk(line: &str) -> Result<((usize, usize), DiskInfo), ScanError> {
+    let (x, y, size, used, avail, percent) = scan!(
+        line,
+        "/dev/grid/node-x{}-y{} {}T {}T {}T {}%",
+        usize,
+        usize,
+        usize,
+        usize,
+        usize,
+        usize
+    )?;
+
+    Ok((
+        (x, y),
+        DiskInfo {
+            size,
+            used,
+            avail,
+            percent,
+        },
+    ))
 }
 
 fn insert_data(
@@ -65,7 +65,7 @@ fn challenge(input: &str) -> (usize, usize) {
     let disks = std::fs::read_to_string(input)
         .unwrap()
         .lines()
-        .map(parse_disk)
+        .map(|line| parse_disk(line).ok())
         .fold(MatrixVec::<DiskInfo>::new(28, 38), insert_data);
 
     let mut part1 = 0;
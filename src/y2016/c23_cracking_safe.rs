@@ -1,17 +1,18 @@
-use crate::utility::assembunny;
+use crate::utility::assembunny::{
+    self,
+    symbolic::{Limits, Outcome, SymbolicMachine},
+};
 
-fn run_seven(code: &Vec<assembunny::Instruction>) -> i64 {
-    let mut vm = assembunny::VirtualMachine::default();
-    *vm.get_register_mut('a') = 7;
-    vm.run(&mut code.clone());
-    *vm.get_register('a')
-}
-
-fn run_twelve(code: &Vec<assembunny::Instruction>) -> i64 {
-    let mut vm = assembunny::VirtualMachine::default();
-    *vm.get_register_mut('a') = 12;
-    vm.run(&mut code.clone());
-    *vm.get_register('a')
+/// Value left in `a` when the program starts with `a` set, the multiplication
+/// loops being run in closed form by the symbolic machine
+fn run(code: &[assembunny::Instruction], a: i64) -> i64 {
+    let limits = Limits {
+        steps: usize::MAX,
+        ..Limits::default()
+    };
+    let path = SymbolicMachine::new(&['a'], a..=a, limits).run(code, &[a]);
+    assert_eq!(path.outcome, Outcome::Halted);
+    path.registers[0].eval(&[a])
 }
 
 fn challenge(input: &str) -> (i64, i64) {
@@ -21,7 +22,7 @@ fn challenge(input: &str) -> (i64, i64) {
         .map(|l| l.parse::<assembunny::Instruction>().unwrap())
         .collect();
 
-    (run_seven(&code), run_twelve(&code))
+    (run(&code, 7), run(&code, 12))
 }
 
 //check_result!("input/Y2016/C23.txt", 0, 0);
@@ -1,8 +1,14 @@
-use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::collections::{BTreeMap, VecDeque};
 
 use nalgebra::Vector2;
 
-use crate::{check_result, utility::matrix::MatrixVec};
+use crate::{
+    check_result,
+    utility::{
+        matrix::MatrixVec,
+        tsp::{self, Problem},
+    },
+};
 
 type Vec2 = Vector2<i64>;
 
@@ -86,55 +92,17 @@ fn build_graph(map: &MatrixVec<u8>, points: &BTreeMap<i64, Vec2>) -> MatrixVec<i
     graph
 }
 
-fn find_shortest(graph: &MatrixVec<i64>, current: usize, unvisited: HashSet<usize>) -> i64 {
-    if unvisited.is_empty() {
-        return 0;
-    }
-
-    let mut result = i64::MAX;
-
-    for &node in &unvisited {
-        let cost = graph.get(current, node).unwrap();
-        let mut set = unvisited.clone();
-        set.remove(&node);
-
-        result = result.min(cost + find_shortest(graph, node, set));
-    }
-
-    result
-}
-
-fn find_shortest2(graph: &MatrixVec<i64>, current: usize, unvisited: HashSet<usize>) -> i64 {
-    if unvisited.is_empty() {
-        return *graph.get(current, 0).unwrap();
-    }
-
-    let mut result = i64::MAX;
-
-    for &node in &unvisited {
-        let cost = graph.get(current, node).unwrap();
-        let mut set = unvisited.clone();
-        set.remove(&node);
-
-        result = result.min(cost + find_shortest2(graph, node, set));
-    }
-
-    result
-}
-
 fn challenge(input: &str) -> (i64, i64) {
     let map = parse_map(input);
     let points = find_check_points(&map);
 
     let graph = build_graph(&map, &points);
 
-    let node_count = (points.last_key_value().unwrap().0 + 1) as usize;
-    let unvisited: HashSet<usize> = (1..node_count).collect();
-
     (
-        find_shortest(&graph, 0, unvisited.clone()),
-        find_shortest2(&graph, 0, unvisited),
+        tsp::solve(&graph, Problem::PathFrom(0)).cost,
+        tsp::solve(&graph, Problem::Tour).cost,
     )
 }
 
 check_result!("input/Y2016/C24.txt", 474, 696);
+
@@ -1,38 +1,31 @@
 use crate::check_result;
+use crate::utility::assembunny::{
+    self,
+    symbolic::{Limits, SymbolicMachine},
+};
 
-fn challenge(_: &str) -> (i64, i64) {
-    let mut test = 2;
+const SIGNAL_LENGTH: usize = 12;
 
-    loop {
-        //println!("{:b}", test);
-        if test >= 2555 {
-            //println!("{}", test - 2555);
-            break;
-        }
-
-        test = test * 4 + 2;
-    }
-
-    (175, 0)
+fn clock_signal() -> Vec<i64> {
+    (0..SIGNAL_LENGTH as i64).map(|i| i % 2).collect()
 }
 
-// fn generate_sequence(input: i64) {
-//     let mut a = 0;
-//     let mut d = input + 365 * 7;
-
-//     // jump a == 0
+fn challenge(input: &str) -> (i64, i64) {
+    let code: Vec<assembunny::Instruction> = std::fs::read_to_string(input)
+        .unwrap()
+        .lines()
+        .map(|l| l.parse::<assembunny::Instruction>().unwrap())
+        .collect();
 
-//     a = d;
+    let limits = Limits {
+        outputs: SIGNAL_LENGTH,
+        ..Limits::default()
+    };
 
-//     // jump a != 0
+    let machine = SymbolicMachine::new(&['a'], 0..=1000, limits);
+    let answer = machine.solve(&code, &clock_signal()).unwrap();
 
-//     c = a % 2;
-//     a = a / 2;
-//     b = 2 - c;
-
-//     println!("{} ", b) // out
-// }
-
-// k = 101010...
+    (answer[0], 0)
+}
 
 check_result!("input/Y2016/C25.txt", 175, 0);
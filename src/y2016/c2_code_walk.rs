@@ -1,7 +1,7 @@
-use crate::check_result;
-use nalgebra::Vector2;
-
-type IVec2 = Vector2<i32>;
+use crate::{
+    check_result,
+    utility::{coords::Keypad, directions::IVec2},
+};
 
 fn parse_dir(c: char) -> IVec2 {
     match c {
@@ -13,47 +13,20 @@ fn parse_dir(c: char) -> IVec2 {
     }
 }
 
-const PANEL: [i32; 9] = [1, 2, 3, 4, 5, 6, 7, 8, 9];
-const PANEL2: [char; 25] = [
-    ' ', ' ', '1', ' ', ' ', ' ', '2', '3', '4', ' ', '5', '6', '7', '8', '9', ' ', 'A', 'B', 'C',
-    ' ', ' ', ' ', 'D', ' ', ' ',
-];
-
-fn part1(items: &Vec<Vec<IVec2>>) -> i64 {
-    let mut digits = Vec::new();
-    let mut current = IVec2::new(1, 1);
-
-    for row in items.iter() {
-        for dir in row.iter() {
-            current = current + *dir;
-
-            current.x = current.x.clamp(0, 2);
-            current.y = current.y.clamp(0, 2);
-        }
+const PANEL: &str = "123\n456\n789";
+const PANEL2: &str = "  1\n 234\n56789\n ABC\n  D";
 
-        digits.push(PANEL[(current.x + current.y * 3) as usize]);
-    }
-
-    digits.iter().fold(0, |acc, &d| acc * 10 + d) as i64
-}
-
-fn part2(items: &Vec<Vec<IVec2>>) -> String {
+fn enter_code(panel: &str, items: &[Vec<IVec2>]) -> String {
+    let keypad = Keypad::new(panel);
     let mut code = String::new();
-    let mut current = IVec2::new(0, 2);
+    let mut current = keypad.find('5').unwrap();
 
     for row in items.iter() {
         for dir in row.iter() {
-            let mut next = current + *dir;
-
-            next.x = next.x.clamp(0, 4);
-            next.y = next.y.clamp(0, 4);
-
-            if PANEL2[(next.x + next.y * 5) as usize] != ' ' {
-                current = next;
-            }
+            current = keypad.step(current, *dir);
         }
 
-        code.push(PANEL2[(current.x + current.y * 5) as usize]);
+        code.push(keypad.key(current).unwrap());
     }
 
     code
@@ -66,7 +39,10 @@ fn challenge(input: &str) -> (i64, String) {
         .map(|line| line.chars().map(parse_dir).collect())
         .collect();
 
-    (part1(&items), part2(&items))
+    (
+        enter_code(PANEL, &items).parse().unwrap(),
+        enter_code(PANEL2, &items),
+    )
 }
 
 check_result!("input/Y2016/C2.txt", 78985, "57DD8".to_string());
@@ -1,4 +1,4 @@
-use crate::check_result;
+use crate::{check_result, utility::count::Count};
 
 struct Marker {
     pub length: usize,
@@ -20,9 +20,10 @@ impl std::str::FromStr for Marker {
     }
 }
 
-fn decompress_count(input: &str) -> (usize, usize) {
-    let mut counter = 0;
-    let mut rec_counter = 0;
+/// Decompressed length without and with expanding nested markers
+fn decompress_count<T: Count>(input: &str) -> (T, T) {
+    let mut counter = T::zero();
+    let mut rec_counter = T::zero();
 
     let mut iter = input.chars();
 
@@ -36,13 +37,14 @@ fn decompress_count(input: &str) -> (usize, usize) {
                 .unwrap();
 
             let data: String = iter.by_ref().take(marker.length).collect();
-            counter += data.len() * marker.repeat;
+            let repeat = T::from_usize_checked(marker.repeat);
+            counter = counter.plus(&T::from_usize_checked(data.len()).times(&repeat));
 
-            let recursive_count = decompress_count(&data).1;
-            rec_counter += recursive_count * marker.repeat;
+            let recursive_count = decompress_count::<T>(&data).1;
+            rec_counter = rec_counter.plus(&recursive_count.times(&repeat));
         } else {
-            counter += 1;
-            rec_counter += 1;
+            counter = counter.plus(&T::one());
+            rec_counter = rec_counter.plus(&T::one());
         }
     }
 
@@ -55,4 +57,19 @@ fn challenge(input: &str) -> (usize, usize) {
     decompress_count(&contents)
 }
 
+#[test]
+fn big_decompression() {
+    use num::BigInt;
+
+    // Three nested markers repeating 2^32 times each
+    let mut data = "A".to_string();
+    for _ in 0..3 {
+        data = format!("({}x{}){}", data.len(), 1u64 << 32, data);
+    }
+
+    let (_, length) = decompress_count::<BigInt>(&data);
+    assert_eq!(length, BigInt::from(1) << 96);
+    assert!(std::panic::catch_unwind(|| decompress_count::<u64>(&data)).is_err());
+}
+
 check_result!("input/Y2016/C9.txt", 152851, 11797310782);
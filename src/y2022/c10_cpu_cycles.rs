@@ -1,38 +1,47 @@
-use crate::{check_result2, utility};
+use crate::{
+    check_result2,
+    utility::{
+        self,
+        vm::{InstructionSet, Machine, Registers, Step},
+    },
+};
 use std::str::FromStr;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 enum Instruction {
     NoOp,
     Add(i32),
 }
 
-fn parse_instruction(s: &str) -> Vec<Instruction> {
+impl InstructionSet for Instruction {
+    type Value = i32;
+
+    fn cycles(&self) -> usize {
+        match self {
+            Instruction::NoOp => 1,
+            Instruction::Add(_) => 2,
+        }
+    }
+
+    fn execute(&self, registers: &mut Registers<i32>, _: usize, _: &mut [Self]) -> Step<i32> {
+        if let Instruction::Add(v) = self {
+            registers[0] += v;
+        }
+        Step::Next
+    }
+}
+
+fn parse_instruction(s: &str) -> Instruction {
     let mut words = s.split(' ');
     let first = words.next().unwrap();
 
     match first {
-        "addx" => vec![
-            Instruction::NoOp,
-            Instruction::Add(words.next().map(i32::from_str).unwrap().unwrap()),
-        ],
-        "noop" => vec![Instruction::NoOp],
+        "addx" => Instruction::Add(words.next().map(i32::from_str).unwrap().unwrap()),
+        "noop" => Instruction::NoOp,
         _ => panic!(),
     }
 }
 
-fn get_register_value_during(instructions: &[Instruction], index: usize) -> i32 {
-    1 + (0..index - 1)
-        .map(|i| {
-            if let Instruction::Add(v) = instructions[i] {
-                v
-            } else {
-                0
-            }
-        })
-        .sum::<i32>()
-}
-
 fn wrap_every_n_chars(s: &str, n: usize) -> String {
     let mut out = String::with_capacity(s.len() + s.len() / n);
     for (i, c) in s.chars().enumerate() {
@@ -47,36 +56,27 @@ fn wrap_every_n_chars(s: &str, n: usize) -> String {
 fn challenge() -> (usize, &'static str) {
     let input = utility::input::get_input(2022, 10).unwrap();
 
-    let instructions = input.lines().fold(Vec::new(), |mut acc, str| {
-        acc.extend(parse_instruction(str));
-        acc
-    });
+    let mut instructions = input.lines().map(parse_instruction).collect::<Vec<_>>();
 
-    let sample_points = vec![20, 60, 100, 140, 180, 220];
+    let sample_points = [20, 60, 100, 140, 180, 220];
     let mut sum = 0;
+    let mut pixels = String::new();
 
-    for sample in sample_points {
-        let sample_sum: i32 = get_register_value_during(&instructions, sample);
-        sum += (sample_sum) * (sample) as i32;
-    }
+    let mut cpu = Machine::new(1);
+    cpu.registers[0] = 1;
+    cpu.run_observed(&mut instructions, |cycle, registers| {
+        let x = registers[0];
 
-    let register_values = (1..=40 * 6)
-        .map(|index| get_register_value_during(&instructions, index))
-        .collect::<Vec<_>>();
-
-    let pixels = register_values
-        .iter()
-        .enumerate()
-        .map(|(index, register)| {
-            let horizontal_pos = index % 40;
-            (horizontal_pos as i32 - register).abs() < 2
-        })
-        .collect::<Vec<_>>();
-
-    let pixels = pixels
-        .iter()
-        .map(|b| if *b { '#' } else { '.' })
-        .collect::<String>();
+        if sample_points.contains(&cycle) {
+            sum += x * cycle as i32;
+        }
+
+        if cycle <= 40 * 6 {
+            let horizontal_pos = (cycle - 1) % 40;
+            let lit = (horizontal_pos as i32 - x).abs() < 2;
+            pixels.push(if lit { '#' } else { '.' });
+        }
+    });
 
     let wrapped = wrap_every_n_chars(&pixels, 40);
     println!("{}", wrapped);
@@ -1,4 +1,7 @@
-use crate::{check_result2, utility};
+use crate::{
+    check_result2,
+    utility::{self, ranges::IntervalSet},
+};
 
 fn parse_range(s: &str) -> ((usize, usize), (usize, usize)) {
     let (e1, e2) = s.split_once(',').unwrap();
@@ -10,15 +13,23 @@ fn parse_range(s: &str) -> ((usize, usize), (usize, usize)) {
     )
 }
 
-fn eval_range(((a1, a2), (b1, b2)): ((usize, usize), (usize, usize))) -> usize {
-    let a_inside = a1 >= b1 && a2 <= b2;
-    let b_inside = b1 >= a1 && b2 <= a2;
+fn to_sets(
+    ((a1, a2), (b1, b2)): ((usize, usize), (usize, usize)),
+) -> (IntervalSet<usize>, IntervalSet<usize>) {
+    (
+        IntervalSet::from_range(a1..=a2),
+        IntervalSet::from_range(b1..=b2),
+    )
+}
 
-    (a_inside || b_inside) as usize
+fn eval_range(ranges: ((usize, usize), (usize, usize))) -> usize {
+    let (a, b) = to_sets(ranges);
+    (a.is_subset(&b) || b.is_subset(&a)) as usize
 }
 
-fn eval_range2(((a1, a2), (b1, b2)): ((usize, usize), (usize, usize))) -> usize {
-    (a1 <= b2 && b1 <= a2) as usize
+fn eval_range2(ranges: ((usize, usize), (usize, usize))) -> usize {
+    let (a, b) = to_sets(ranges);
+    a.intersects(&b) as usize
 }
 
 fn challenge() -> (usize, usize) {
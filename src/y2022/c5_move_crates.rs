@@ -1,12 +1,9 @@
 use std::collections::VecDeque;
 
-use crate::{check_result2, utility};
-use lazy_static::lazy_static;
-use regex::Regex;
-
-lazy_static! {
-    static ref INSTRUCTION_REGEX: Regex = Regex::new(r"^move (\d+) from (\d+) to (\d+)$").unwrap();
-}
+use crate::{
+    check_result2, scan,
+    utility::{self, parsing::ScanError},
+};
 
 fn parse_state(lines: &[&str]) -> Vec<VecDeque<char>> {
     let stack_count = (lines.first().unwrap().len() + 2) / 4;
@@ -33,16 +30,8 @@ fn parse_state(lines: &[&str]) -> Vec<VecDeque<char>> {
 }
 
 // count, src, dst
-fn parse_commands(lines: &str) -> Option<(usize, usize, usize)> {
-    if let Some(captures) = INSTRUCTION_REGEX.captures(lines) {
-        Some((
-            captures[1].parse().ok()?,
-            captures[2].parse().ok()?,
-            captures[3].parse().ok()?,
-        ))
-    } else {
-        None
-    }
+fn parse_commands(line: &str) -> Result<(usize, usize, usize), ScanError> {
+    scan!(line, "move {} from {} to {}", usize, usize, usize)
 }
 
 fn get_top(state: &Vec<VecDeque<char>>) -> String {
@@ -1,8 +1,5 @@
-use crate::utility::tree::Node;
+use crate::utility::vfs::Vfs;
 use crate::{check_result2, utility};
-use std::ops::Deref;
-use std::rc::Rc;
-use std::str::FromStr;
 
 const TEST: &str = r#"$ cd /
 $ ls
@@ -28,151 +25,22 @@ $ ls
 5626152 d.ext
 7214296 k"#;
 
-#[derive(Debug)]
-enum Item {
-    Directory(String),
-    File(String, usize),
-}
-
-impl Item {
-    fn name(&self) -> &str {
-        match self {
-            Item::Directory(name) => name,
-            Item::File(name, _) => name,
-        }
-    }
-}
-
-#[derive(Debug)]
-enum Command {
-    Ls(Vec<Item>),
-    Cd(String),
-}
-
-impl FromStr for Command {
-    type Err = String;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let command = if s.starts_with("ls") {
-            Command::Ls(s.lines().skip(1).map(|e| e.parse().unwrap()).collect())
-        } else if s.starts_with("cd") {
-            Command::Cd(s.trim().split(' ').skip(1).next().unwrap().to_string())
-        } else {
-            panic!("Found unknown command {}", &s);
-        };
-        Ok(command)
-    }
-}
-
-impl FromStr for Item {
-    type Err = String;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let words = s.split(' ').collect::<Vec<_>>();
-        Ok(match words[0] {
-            "dir" => Item::Directory(words[1].to_string()),
-            _ => Item::File(words[1].to_string(), words[0].parse().unwrap()),
-        })
-    }
-}
-
-fn parse_filesystem(lines: &str) -> Rc<Node<Item>> {
-    let commands = lines
-        .split("$ ")
-        .skip(1)
-        .map(|e| e.parse().unwrap())
-        .collect::<Vec<Command>>();
-    let root = Node::new(Item::Directory("/".to_string()));
-    let mut current = root.clone();
-
-    for command in commands {
-        match command {
-            Command::Cd(target) => match target.as_str() {
-                ".." => current = current.parent().unwrap(),
-                "/" => current = root.clone(),
-                _ => {
-                    let found = current
-                        .children()
-                        .iter()
-                        .find(|&e| target == e.name())
-                        .cloned();
-
-                    if let Some(found) = found {
-                        current = found.clone()
-                    } else {
-                        let new = Node::new(Item::Directory(target.clone()));
-                        current = new.clone();
-                        Node::add_child(&current, new);
-                    }
-                }
-            },
-            Command::Ls(contents) => {
-                for item in contents {
-                    let found = current
-                        .children()
-                        .iter()
-                        .find(|&e| item.name() == e.name())
-                        .cloned();
-
-                    if found.is_none() {
-                        Node::add_child(&current, Node::new(item));
-                    }
-                }
-            }
-        }
-    }
-
-    root
-}
-
-fn visit<F>(item: &Rc<Node<Item>>, visitor: &mut F)
-where
-    F: FnMut(&Node<Item>),
-{
-    visitor(item.deref());
-    for child in item.children().iter() {
-        visit(child, visitor);
-    }
-}
-
-fn directory_size(node: &Node<Item>) -> usize {
-    let mut accum = 0;
-    for child in node.children().iter() {
-        match child.deref().deref() {
-            Item::Directory(_) => accum += directory_size(child),
-            Item::File(_, size) => accum += size,
-        }
-    }
-    accum
-}
-
 fn challenge() -> (usize, usize) {
     let input: String = utility::input::get_input(2022, 7).unwrap();
-    let root = parse_filesystem(&input);
-
-    let mut directories: Vec<(String, usize)> = vec![];
-
-    let mut visitor = |node: &Node<Item>| match node.deref() {
-        Item::Directory(name) => directories.push((name.clone(), directory_size(node))),
-        _ => {}
-    };
-
-    visit(&root, &mut visitor);
-    let sum = directories
-        .iter()
-        .skip(1)
-        .filter(|(_, size)| *size <= 100000)
-        .fold(0, |a, (_, s)| a + s);
-    let total_occupied = directories.first().unwrap().1;
-    let total_free = 70000000 - total_occupied;
-    let total_necessary = 30000000 - total_free;
-
-    let min_necessary = directories
-        .iter()
-        .map(|(_, s)| s)
-        .filter(|s| **s >= total_necessary)
-        .min()
+    let vfs = Vfs::from_transcript(&input).unwrap();
+
+    let sum = vfs
+        .directories_at_most(100000)
+        .into_iter()
+        .map(|d| vfs.size(d))
+        .sum::<u64>();
+    let min_necessary = vfs
+        .smallest_to_free(70000000, 30000000)
+        .map(|d| vfs.size(d))
         .unwrap();
 
-    (sum, *min_necessary)
+    (sum as usize, min_necessary as usize)
 }
 
 check_result2!(1844187, 4978279);
+
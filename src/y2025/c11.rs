@@ -1,6 +1,6 @@
-use std::collections::{HashMap, HashSet};
+use num::BigInt;
 
-use crate::{check_result2, utility};
+use crate::utility::{self, count::Answer, graph::Graph};
 
 const TEST: &str = r#"aaa: you hhh
 you: bbb ccc
@@ -27,147 +27,66 @@ fff: ggg hhh
 ggg: out
 hhh: out"#;
 
-#[derive(Debug, Clone)]
-struct Node {
-    edges: Vec<String>,
-}
-
-type NodeMap = HashMap<String, Node>;
-
-fn parse_line(l: &str) -> (String, Node) {
-    let (name, sequence) = l.split_at(l.find(':').unwrap());
-    let sequence = &sequence[2..];
-
-    (
-        name.to_string(),
-        Node {
-            edges: sequence.split(' ').map(|s| s.to_string()).collect(),
-        },
-    )
-}
-
-fn inverted_graph(map: &NodeMap) -> NodeMap {
-    let mut new_map: NodeMap = HashMap::new();
-
-    for (name, edges) in map {
-        for edge in edges.edges.iter() {
-            new_map
-                .entry(edge.to_string())
-                .and_modify(|e| e.edges.push(name.to_string()))
-                .or_insert(Node {
-                    edges: vec![name.to_string()],
-                });
+fn parse_graph(input: &str) -> Graph<String> {
+    let mut graph = Graph::new();
+    for line in input.lines() {
+        let (name, outputs) = line.split_once(": ").unwrap();
+        for output in outputs.split(' ') {
+            graph.connect(name.to_string(), output.to_string());
         }
     }
-
-    new_map
+    graph
 }
 
-fn count_paths(map: &NodeMap, current: &str, target: &str) -> usize {
-    if current == target {
-        return 1;
-    }
-
-    if let Some(node) = map.get(current) {
-        let mut count = 0;
-        for edge in node.edges.iter() {
-            count += count_paths(map, &edge, target);
-        }
-        return count;
-    }
-
-    return 0;
-}
-
-fn count_paths_fft_dac(
-    map: &NodeMap,
-    current: &str,
-    target: &str,
-    mut reached: [bool; 2],
-) -> usize {
-    if current == target && reached.iter().all(|v| *v) {
-        return 1;
-    } else if current == target {
-        return 0;
-    }
-
-    if current == "fft" {
-        reached[0] = true;
-    }
-
-    if current == "dac" {
-        reached[1] = true;
+fn paths(graph: &Graph<String>, from: &str, to: &str) -> usize {
+    match (graph.id(&from.to_string()), graph.id(&to.to_string())) {
+        (Some(from), Some(to)) => graph.count_paths(from, to).expect("Paths run into a cycle"),
+        _ => 0,
     }
-
-    if let Some(node) = map.get(current) {
-        let mut count = 0;
-        for edge in node.edges.iter() {
-            count += count_paths_fft_dac(map, &edge, target, reached);
-        }
-        return count;
-    }
-
-    return 0;
 }
 
-type MemoTable = HashMap<String, usize>;
-fn count_paths_memo(map: &NodeMap, start: &str, target: &str) -> usize {
-    fn count_paths_inner(
-        memo: &mut MemoTable,
-        map: &NodeMap,
-        current: &str,
-        target: &str,
-    ) -> usize {
-        if current == target {
-            return 1;
-        }
-
-        if let Some(node) = map.get(current) {
-            let mut count = 0;
-            for edge in node.edges.iter() {
-                let result = if let Some(&memo_value) = memo.get(edge) {
-                    memo_value
-                } else {
-                    let value = count_paths_inner(memo, map, &edge, target);
-                    memo.insert(edge.clone(), value);
-                    value
-                };
-
-                count += result;
-            }
-            return count;
-        }
-
-        return 0;
-    }
-
-    let mut memo = MemoTable::new();
-    let result = count_paths_inner(&mut memo, map, start, target);
-    result
+/// Paths from the server to the output that pass through both fft and dac, in any order
+fn problem_paths(graph: &Graph<String>) -> BigInt {
+    let id = |name: &str| graph.id(&name.to_string()).unwrap();
+    graph
+        .count_paths_through(id("svr"), id("out"), &[id("fft"), id("dac")])
+        .expect("Paths run into a cycle")
 }
 
-fn challenge() -> (usize, usize) {
+fn challenge() -> (usize, Answer) {
     let input = utility::input::get_input(2025, 11).unwrap();
+    let graph = parse_graph(&input);
 
-    let map: HashMap<String, Node> = input.lines().map(parse_line).collect();
-    let inverted = inverted_graph(&map);
-
-    //dbg!(&map);
-
-    // let mut answer1 = 0;
-    //let mut answer2 = 0;
+    let answer1 = paths(&graph, "you", "out");
+    let answer2 = Answer::from(problem_paths(&graph));
 
-    let answer1 = count_paths_memo(&map, "you", "out");
-
-    let answer2 = {
-        let svr_fft = count_paths_memo(&map, "svr", "fft");
-        let fft_dac = count_paths_memo(&map, "fft", "dac");
-        let dac_out = count_paths_memo(&map, "dac", "out");
+    (answer1, answer2)
+}
 
-        svr_fft * fft_dac * dac_out
+/// Part 2 counted leg by leg, svr to out through fft then dac or through dac then fft
+fn paths_by_legs(graph: &Graph<String>) -> BigInt {
+    let legs = |stops: [&str; 4]| {
+        stops
+            .windows(2)
+            .map(|leg| BigInt::from(paths(graph, leg[0], leg[1])))
+            .product::<BigInt>()
     };
+    legs(["svr", "fft", "dac", "out"]) + legs(["svr", "dac", "fft", "out"])
+}
 
-    (answer1, answer2)
+// The part 2 answer for this input has not been recorded, so it is checked against
+// counting the legs separately instead of a fixed number
+#[test]
+fn check_results() {
+    let (answer1, answer2) = challenge();
+    let graph = parse_graph(&utility::input::get_input(2025, 11).unwrap());
+    assert_eq!(answer1, 552);
+    assert_eq!(answer2, Answer::from(paths_by_legs(&graph)));
 }
 
-check_result2!(552, 0);
+#[test]
+fn samples() {
+    assert_eq!(paths(&parse_graph(TEST), "you", "out"), 5);
+    assert_eq!(problem_paths(&parse_graph(TEST2)), BigInt::from(2));
+    assert_eq!(paths_by_legs(&parse_graph(TEST2)), BigInt::from(2));
+}
@@ -1,6 +1,6 @@
 use crate::{
     check_result2,
-    utility::{self, modular::get_divisors},
+    utility::{self, primes},
 };
 
 fn parse_range(r: &str) -> (i64, i64) {
@@ -22,10 +22,10 @@ fn eval_number(n: i64) -> bool {
 fn eval_number2(n: i64) -> bool {
     let s = n.to_string();
     let chars: Vec<char> = s.chars().collect();
-    let mut divisors = get_divisors(chars.len());
-    divisors.pop();
 
-    for div in divisors {
+    // Every proper divisor of the digit count is a candidate part length
+    let divisors = primes::divisors(chars.len() as u64);
+    for div in divisors[..divisors.len() - 1].iter().map(|d| *d as usize) {
         let first = &chars[0..div];
         let splits = chars.chunks(div).collect::<Vec<_>>();
         if splits.iter().all(|e| *e == first) {
@@ -1,6 +1,6 @@
 use crate::{
     check_result2,
-    utility::{self, matrix::MatrixVec},
+    utility::{self, count::Count, matrix::MatrixVec},
 };
 
 const TEST: &str = r#".......S.......
@@ -20,6 +20,42 @@ const TEST: &str = r#".......S.......
 .^.^.^.^.^...^.
 ..............."#;
 
+/// Number of timelines reaching the bottom row, every splitter doubling the ones that hit it
+fn count_timelines<T: Count>(grid: &MatrixVec<char>) -> T {
+    let mut timelines = grid.map(|c| match *c {
+        'S' => T::one(),
+        '.' | '^' => T::zero(),
+        _ => panic!(),
+    });
+
+    for r in 1..grid.rows() {
+        for c in 0..grid.cols() {
+            let up = timelines.get(r - 1, c).unwrap().clone();
+            if up.is_zero() {
+                continue;
+            }
+
+            match *grid.get(r, c).unwrap() {
+                '.' => {
+                    let tile = timelines.get_mut(r, c).unwrap();
+                    *tile = tile.plus(&up);
+                }
+                '^' => {
+                    let left = timelines.get_mut(r, c - 1).unwrap();
+                    *left = left.plus(&up);
+                    let right = timelines.get_mut(r, c + 1).unwrap();
+                    *right = right.plus(&up);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    timelines
+        .row_iter(timelines.rows() - 1)
+        .fold(T::zero(), |acc, v| acc.plus(v))
+}
+
 fn challenge() -> (i64, i64) {
     let input = utility::input::get_input(2025, 7).unwrap();
     let grid = MatrixVec::from_string(&input);
@@ -56,48 +92,18 @@ fn challenge() -> (i64, i64) {
         }
     }
 
-    let answer2;
-
-    {
-        let igrid = grid
-            .iter()
-            .map(|c| match *c {
-                'S' => 1,
-                '.' => 0,
-                '^' => 0,
-                _ => panic!(),
-            })
-            .collect::<Vec<i64>>();
-
-        let mut igrid = MatrixVec::from_vec(grid.rows(), grid.cols(), igrid);
-
-        for r in 1..grid.rows() {
-            for c in 0..grid.cols() {
-                let up = *igrid.get(r - 1, c).unwrap();
-                if up == 0 {
-                    continue;
-                }
-
-                let tile = grid.get(r, c).unwrap();
-
-                match *tile {
-                    '.' => {
-                        *igrid.get_mut(r, c).unwrap() += up;
-                    }
-                    '^' => {
-                        let left = igrid.get_mut(r, c - 1).unwrap();
-                        *left += up;
-                        let right = igrid.get_mut(r, c + 1).unwrap();
-                        *right += up;
-                    }
-                    _ => {}
-                }
-            }
-        }
-        answer2 = igrid.row_iter(igrid.rows() - 1).sum();
-    }
+    let answer2 = count_timelines::<i64>(&grid);
 
     (answer1, answer2)
 }
 
 check_result2!(1675, 187987920774390);
+
+#[test]
+fn big_timelines() {
+    use num::BigInt;
+
+    let grid = MatrixVec::from_string(TEST);
+    assert_eq!(count_timelines::<u8>(&grid), 40);
+    assert_eq!(count_timelines::<BigInt>(&grid), BigInt::from(40));
+}